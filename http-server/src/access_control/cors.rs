@@ -309,6 +309,7 @@ lazy_static! {
 		hs.insert(Ascii::new("Accept-Language"));
 		hs.insert(Ascii::new("Access-Control-Allow-Origin"));
 		hs.insert(Ascii::new("Access-Control-Request-Headers"));
+		hs.insert(Ascii::new("Access-Control-Request-Method"));
 		hs.insert(Ascii::new("Content-Language"));
 		hs.insert(Ascii::new("Content-Type"));
 		hs.insert(Ascii::new("Host"));
@@ -49,14 +49,36 @@ pub struct AccessControl {
 impl AccessControl {
 	/// Validate incoming request by http HOST
 	pub fn deny_host(&self, request: &hyper::Request<hyper::Body>) -> bool {
-		!hosts::is_host_valid(http_helpers::read_header_value(request.headers(), "host"), &self.allowed_hosts)
+		!hosts::is_host_valid(request_host(request), &self.allowed_hosts)
 	}
 
 	/// Validate incoming request by CORS origin
 	pub fn deny_cors_origin(&self, request: &hyper::Request<hyper::Body>) -> bool {
-		let header = cors::get_cors_allow_origin(
+		self.cors_allow_origin(request) == cors::AllowCors::Invalid && !self.continue_on_invalid_cors
+	}
+
+	/// Validate incoming request by CORS header
+	pub fn deny_cors_header(&self, request: &hyper::Request<hyper::Body>) -> bool {
+		self.cors_allow_headers(request) == cors::AllowCors::Invalid && !self.continue_on_invalid_cors
+	}
+
+	/// Value of the `access-control-allow-origin` header to send back for the request, if any.
+	pub(crate) fn allowed_cors_origin(&self, request: &hyper::Request<hyper::Body>) -> Option<header::HeaderValue> {
+		self.cors_allow_origin(request).into()
+	}
+
+	/// Values of the `access-control-allow-headers` header to send back for the request, if any.
+	pub(crate) fn allowed_cors_headers(
+		&self,
+		request: &hyper::Request<hyper::Body>,
+	) -> Option<Vec<header::HeaderValue>> {
+		self.cors_allow_headers(request).into()
+	}
+
+	fn cors_allow_origin(&self, request: &hyper::Request<hyper::Body>) -> cors::AllowCors<header::HeaderValue> {
+		cors::get_cors_allow_origin(
 			http_helpers::read_header_value(request.headers(), "origin"),
-			http_helpers::read_header_value(request.headers(), "host"),
+			request_host(request),
 			&self.allowed_origins,
 		)
 		.map(|origin| {
@@ -68,22 +90,30 @@ impl AccessControl {
 				Null => header::HeaderValue::from_static("null"),
 				Any => header::HeaderValue::from_static("*"),
 			}
-		});
-		header == cors::AllowCors::Invalid && !self.continue_on_invalid_cors
+		})
 	}
 
-	/// Validate incoming request by CORS header
-	pub fn deny_cors_header(&self, request: &hyper::Request<hyper::Body>) -> bool {
+	fn cors_allow_headers(&self, request: &hyper::Request<hyper::Body>) -> cors::AllowCors<Vec<header::HeaderValue>> {
 		let headers = request.headers().keys().map(|name| name.as_str());
 		let requested_headers = http_helpers::read_header_values(request.headers(), "access-control-request-headers")
 			.filter_map(|val| val.to_str().ok())
 			.flat_map(|val| val.split(", "))
 			.flat_map(|val| val.split(','));
 
-		let header = cors::get_cors_allow_headers(headers, requested_headers, &self.allowed_headers, |name| {
+		cors::get_cors_allow_headers(headers, requested_headers, &self.allowed_headers, |name| {
 			header::HeaderValue::from_str(name).unwrap_or_else(|_| header::HeaderValue::from_static("unknown"))
-		});
-		header == cors::AllowCors::Invalid && !self.continue_on_invalid_cors
+		})
+	}
+}
+
+/// Host the request is addressed to: the `host` header, or the URI authority for HTTP/2 requests which carry
+/// the host in the `:authority` pseudo-header instead.
+fn request_host(request: &hyper::Request<hyper::Body>) -> Option<&str> {
+	match http_helpers::read_header_value(request.headers(), "host") {
+		None if request.version() == hyper::Version::HTTP_2 => {
+			request.uri().authority().map(|authority| authority.as_str())
+		}
+		host => host,
 	}
 }
 
@@ -46,10 +46,21 @@ pub fn host_not_allowed() -> hyper::Response<hyper::Body> {
 
 /// Create a text/plain response for disallowed method used.
 pub fn method_not_allowed() -> hyper::Response<hyper::Body> {
-	from_template(
+	let mut response = from_template(
 		hyper::StatusCode::METHOD_NOT_ALLOWED,
 		"Used HTTP Method is not allowed. POST or OPTIONS is required\n".to_owned(),
 		TEXT,
+	);
+	response.headers_mut().insert(hyper::header::ALLOW, hyper::header::HeaderValue::from_static("POST, OPTIONS"));
+	response
+}
+
+/// Create a text/plain response for requests with a content type other than JSON (415).
+pub fn unsupported_content_type() -> hyper::Response<hyper::Body> {
+	from_template(
+		hyper::StatusCode::UNSUPPORTED_MEDIA_TYPE,
+		"Supplied content type is not allowed. Content-Type: application/json is required\n".to_owned(),
+		TEXT,
 	)
 }
 
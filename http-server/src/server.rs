@@ -26,7 +26,7 @@
 
 use crate::{response, AccessControl};
 use futures_channel::mpsc;
-use futures_util::{future::join_all, stream::StreamExt, FutureExt};
+use futures_util::{stream::StreamExt, FutureExt};
 use hyper::{
 	server::{conn::AddrIncoming, Builder as HyperBuilder},
 	service::{make_service_fn, service_fn},
@@ -35,12 +35,13 @@ use hyper::{
 use jsonrpsee_types::{
 	error::{Error, GenericTransportError},
 	middleware::Middleware,
-	v2::{ErrorCode, Id, Notification, Request},
+	to_json_raw_value,
+	v2::{ErrorCode, ErrorObject, Id, Notification, Request, Response},
 	TEN_MB_SIZE_BYTES,
 };
 use jsonrpsee_utils::http_helpers::read_body;
 use jsonrpsee_utils::server::{
-	helpers::{collect_batch_response, prepare_error, MethodSink},
+	helpers::{execute_batch, prepare_error, split_batch, MethodSink},
 	resource_limiting::Resources,
 	rpc_module::{MethodResult, Methods},
 };
@@ -53,14 +54,25 @@ use std::{
 	net::{SocketAddr, TcpListener, ToSocketAddrs},
 	pin::Pin,
 	task::{Context, Poll},
+	time::Duration,
 };
 
+/// Default maximum number of calls in a batch request.
+const MAX_BATCH_LEN: usize = 1024;
+
 /// Builder to create JSON-RPC HTTP server.
 #[derive(Debug)]
 pub struct Builder<M = ()> {
 	access_control: AccessControl,
 	resources: Resources,
 	max_request_body_size: u32,
+	max_batch_len: usize,
+	batch_request_timeout: Option<Duration>,
+	allow_missing_content_type: bool,
+	health_api: Option<HealthApi>,
+	http2_max_concurrent_streams: Option<u32>,
+	http2_initial_stream_window_size: Option<u32>,
+	http2_initial_connection_window_size: Option<u32>,
 	keep_alive: bool,
 	/// Custom tokio runtime to run the server on.
 	tokio_runtime: Option<tokio::runtime::Handle>,
@@ -71,6 +83,13 @@ impl Default for Builder {
 	fn default() -> Self {
 		Self {
 			max_request_body_size: TEN_MB_SIZE_BYTES,
+			max_batch_len: MAX_BATCH_LEN,
+			batch_request_timeout: None,
+			allow_missing_content_type: false,
+			health_api: None,
+			http2_max_concurrent_streams: None,
+			http2_initial_stream_window_size: None,
+			http2_initial_connection_window_size: None,
 			resources: Resources::default(),
 			access_control: AccessControl::default(),
 			keep_alive: true,
@@ -115,6 +134,13 @@ impl<M> Builder<M> {
 	pub fn set_middleware<T: Middleware>(self, middleware: T) -> Builder<T> {
 		Builder {
 			max_request_body_size: self.max_request_body_size,
+			max_batch_len: self.max_batch_len,
+			batch_request_timeout: self.batch_request_timeout,
+			allow_missing_content_type: self.allow_missing_content_type,
+			health_api: self.health_api,
+			http2_max_concurrent_streams: self.http2_max_concurrent_streams,
+			http2_initial_stream_window_size: self.http2_initial_stream_window_size,
+			http2_initial_connection_window_size: self.http2_initial_connection_window_size,
 			resources: self.resources,
 			access_control: self.access_control,
 			keep_alive: self.keep_alive,
@@ -129,6 +155,58 @@ impl<M> Builder<M> {
 		self
 	}
 
+	/// Sets the maximum number of calls allowed in a batch request (default is 1024).
+	///
+	/// Batches exceeding the limit are rejected with a single `Invalid request` error.
+	pub fn max_batch_len(mut self, len: usize) -> Self {
+		self.max_batch_len = len;
+		self
+	}
+
+	/// Sets a deadline for answering all calls in a batch request, measured from when the batch was received.
+	///
+	/// Calls that haven't completed once the deadline has passed are answered with an `Internal error` so that
+	/// the batch response can be sent. Disabled by default.
+	pub fn batch_request_timeout(mut self, timeout: Duration) -> Self {
+		self.batch_request_timeout = Some(timeout);
+		self
+	}
+
+	/// Accept requests without a `Content-Type` header, for clients that don't send one.
+	///
+	/// Requests with a `Content-Type` other than JSON are still rejected. Disabled by default.
+	pub fn allow_missing_content_type(mut self, allow: bool) -> Self {
+		self.allow_missing_content_type = allow;
+		self
+	}
+
+	/// Enables a health endpoint: `GET` requests to `path` are answered with the result of calling the RPC `method`
+	/// without parameters, and `500 Internal Server Error` if the method fails. The call bypasses the middleware.
+	///
+	/// Disabled by default.
+	pub fn health_api(mut self, path: impl Into<String>, method: impl Into<String>) -> Self {
+		self.health_api = Some(HealthApi { path: path.into(), method: method.into() });
+		self
+	}
+
+	/// Sets the maximum number of concurrent HTTP/2 streams per connection. Uses hyper's default if not set.
+	pub fn http2_max_concurrent_streams(mut self, max: u32) -> Self {
+		self.http2_max_concurrent_streams = Some(max);
+		self
+	}
+
+	/// Sets the initial HTTP/2 stream-level flow control window size in bytes. Uses hyper's default if not set.
+	pub fn http2_initial_stream_window_size(mut self, size: u32) -> Self {
+		self.http2_initial_stream_window_size = Some(size);
+		self
+	}
+
+	/// Sets the initial HTTP/2 connection-level flow control window size in bytes. Uses hyper's default if not set.
+	pub fn http2_initial_connection_window_size(mut self, size: u32) -> Self {
+		self.http2_initial_connection_window_size = Some(size);
+		self
+	}
+
 	/// Sets access control settings.
 	pub fn set_access_control(mut self, acl: AccessControl) -> Self {
 		self.access_control = acl;
@@ -189,21 +267,58 @@ impl<M> Builder<M> {
 				}
 			};
 
-			return Ok(Server {
-				listener,
-				local_addr,
-				access_control: self.access_control,
-				max_request_body_size: self.max_request_body_size,
-				resources: self.resources,
-				tokio_runtime: self.tokio_runtime,
-				middleware: self.middleware,
-			});
+			return Ok(self.into_server(listener, local_addr));
 		}
 
 		let err = err.unwrap_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "No address found").into());
 		Err(err)
 	}
 
+	/// Finalizes the configuration of the server with a listener that is already bound, for example one handed
+	/// over by the init system. The `keep_alive` setting and `TCP_NODELAY` are applied to the listener like with
+	/// [`Builder::build`], other socket options such as the backlog are left as the caller configured them.
+	///
+	/// ```rust
+	/// #[tokio::main]
+	/// async fn main() {
+	///   let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+	///   let addr = listener.local_addr().unwrap();
+	///   let server = jsonrpsee_http_server::HttpServerBuilder::default().build_from_tcp(listener).unwrap();
+	///   assert_eq!(server.local_addr().unwrap(), addr);
+	/// }
+	/// ```
+	pub fn build_from_tcp(self, listener: impl Into<TcpListener>) -> Result<Server<M>, Error> {
+		let listener = listener.into();
+		let socket = socket2::SockRef::from(&listener);
+		socket.set_nodelay(true)?;
+		socket.set_keepalive(self.keep_alive)?;
+		listener.set_nonblocking(true)?;
+		let local_addr = listener.local_addr().ok();
+		let listener = hyper::Server::from_tcp(listener)?;
+		Ok(self.into_server(listener, local_addr))
+	}
+
+	fn into_server(self, listener: HyperBuilder<AddrIncoming>, local_addr: Option<SocketAddr>) -> Server<M> {
+		let listener = listener
+			.http2_max_concurrent_streams(self.http2_max_concurrent_streams)
+			.http2_initial_stream_window_size(self.http2_initial_stream_window_size)
+			.http2_initial_connection_window_size(self.http2_initial_connection_window_size);
+
+		Server {
+			listener,
+			local_addr,
+			access_control: self.access_control,
+			max_request_body_size: self.max_request_body_size,
+			max_batch_len: self.max_batch_len,
+			batch_request_timeout: self.batch_request_timeout,
+			allow_missing_content_type: self.allow_missing_content_type,
+			health_api: self.health_api,
+			resources: self.resources,
+			tokio_runtime: self.tokio_runtime,
+			middleware: self.middleware,
+		}
+	}
+
 	fn inner_builder(
 		&self,
 		addr: SocketAddr,
@@ -225,6 +340,19 @@ impl<M> Builder<M> {
 	}
 }
 
+/// Health endpoint configuration, see [`Builder::health_api`].
+#[derive(Debug, Clone)]
+struct HealthApi {
+	path: String,
+	method: String,
+}
+
+impl HealthApi {
+	fn matches(&self, request: &hyper::Request<hyper::Body>) -> bool {
+		request.method() == hyper::Method::GET && request.uri().path() == self.path
+	}
+}
+
 /// Handle used to run or stop the server.
 #[derive(Debug)]
 pub struct ServerHandle {
@@ -265,6 +393,14 @@ pub struct Server<M = ()> {
 	local_addr: Option<SocketAddr>,
 	/// Max request body size.
 	max_request_body_size: u32,
+	/// Max number of calls in a batch request.
+	max_batch_len: usize,
+	/// Deadline for answering all calls in a batch request.
+	batch_request_timeout: Option<Duration>,
+	/// Accept requests without a `Content-Type` header.
+	allow_missing_content_type: bool,
+	/// Health endpoint, if enabled.
+	health_api: Option<HealthApi>,
 	/// Access control
 	access_control: AccessControl,
 	/// Tracker for currently used resources on the server
@@ -283,6 +419,10 @@ impl<M: Middleware> Server<M> {
 	/// Start the server.
 	pub fn start(mut self, methods: impl Into<Methods>) -> Result<ServerHandle, Error> {
 		let max_request_body_size = self.max_request_body_size;
+		let max_batch_len = self.max_batch_len;
+		let batch_request_timeout = self.batch_request_timeout;
+		let allow_missing_content_type = self.allow_missing_content_type;
+		let health_api = self.health_api;
 		let access_control = self.access_control;
 		let (tx, mut rx) = mpsc::channel(1);
 		let listener = self.listener;
@@ -293,6 +433,7 @@ impl<M: Middleware> Server<M> {
 		let make_service = make_service_fn(move |_| {
 			let methods = methods.clone();
 			let access_control = access_control.clone();
+			let health_api = health_api.clone();
 			let resources = resources.clone();
 			let middleware = middleware.clone();
 
@@ -300,6 +441,7 @@ impl<M: Middleware> Server<M> {
 				Ok::<_, HyperError>(service_fn(move |request| {
 					let methods = methods.clone();
 					let access_control = access_control.clone();
+					let health_api = health_api.clone();
 					let resources = resources.clone();
 					let middleware = middleware.clone();
 
@@ -310,107 +452,35 @@ impl<M: Middleware> Server<M> {
 							return Ok::<_, HyperError>(e);
 						}
 
-						if let Err(e) = content_type_is_valid(&request) {
-							return Ok::<_, HyperError>(e);
+						if let Some(health_api) = health_api.filter(|health_api| health_api.matches(&request)) {
+							let response =
+								process_health_request(&health_api, &methods, &resources, max_request_body_size).await;
+							return Ok::<_, HyperError>(response);
+						}
+
+						if request.method() == hyper::Method::OPTIONS {
+							return Ok::<_, HyperError>(cors_preflight_response(&access_control, &request));
 						}
 
-						let (parts, body) = request.into_parts();
-
-						let (body, mut is_single) = match read_body(&parts.headers, body, max_request_body_size).await {
-							Ok(r) => r,
-							Err(GenericTransportError::TooLarge) => return Ok::<_, HyperError>(response::too_large()),
-							Err(GenericTransportError::Malformed) => return Ok::<_, HyperError>(response::malformed()),
-							Err(GenericTransportError::Inner(e)) => {
-								tracing::error!("Internal error reading request body: {}", e);
-								return Ok::<_, HyperError>(response::internal_error());
-							}
-						};
-
-						let request_start = middleware.on_request();
-
-						// NOTE(niklasad1): it's a channel because it's needed for batch requests.
-						let (tx, mut rx) = mpsc::unbounded::<String>();
-						let sink = MethodSink::new_with_limit(tx, max_request_body_size);
-
-						type Notif<'a> = Notification<'a, Option<&'a RawValue>>;
-
-						// Single request or notification
-						if is_single {
-							if let Ok(req) = serde_json::from_slice::<Request>(&body) {
-								middleware.on_call(req.method.as_ref());
-
-								// NOTE: we don't need to track connection id on HTTP, so using hardcoded 0 here.
-								match methods.execute_with_resources(&sink, req, 0, &resources) {
-									Ok((name, MethodResult::Sync(success))) => {
-										middleware.on_result(name, success, request_start);
-									}
-									Ok((name, MethodResult::Async(fut))) => {
-										let success = fut.await;
-
-										middleware.on_result(name, success, request_start);
-									}
-									Err(name) => {
-										middleware.on_result(name.as_ref(), false, request_start);
-									}
-								}
-							} else if let Ok(_req) = serde_json::from_slice::<Notif>(&body) {
-								return Ok::<_, HyperError>(response::ok_response("".into()));
-							} else {
-								let (id, code) = prepare_error(&body);
-								sink.send_error(id, code.into());
-							}
-
-						// Batch of requests or notifications
-						} else if let Ok(batch) = serde_json::from_slice::<Vec<Request>>(&body) {
-							if !batch.is_empty() {
-								let middleware = &middleware;
-
-								join_all(batch.into_iter().filter_map(move |req| {
-									match methods.execute_with_resources(&sink, req, 0, &resources) {
-										Ok((name, MethodResult::Sync(success))) => {
-											middleware.on_result(name, success, request_start);
-											None
-										}
-										Ok((name, MethodResult::Async(fut))) => Some(async move {
-											let success = fut.await;
-											middleware.on_result(name, success, request_start);
-										}),
-										Err(name) => {
-											middleware.on_result(name.as_ref(), false, request_start);
-											None
-										}
-									}
-								}))
-								.await;
-							} else {
-								// "If the batch rpc call itself fails to be recognized as an valid JSON or as an
-								// Array with at least one value, the response from the Server MUST be a single
-								// Response object." – The Spec.
-								is_single = true;
-								sink.send_error(Id::Null, ErrorCode::InvalidRequest.into());
-							}
-						} else if let Ok(_batch) = serde_json::from_slice::<Vec<Notif>>(&body) {
-							return Ok::<_, HyperError>(response::ok_response("".into()));
-						} else {
-							// "If the batch rpc call itself fails to be recognized as an valid JSON or as an
-							// Array with at least one value, the response from the Server MUST be a single
-							// Response object." – The Spec.
-							is_single = true;
-							let (id, code) = prepare_error(&body);
-							sink.send_error(id, code.into());
+						if let Err(e) = content_type_is_valid(&request, allow_missing_content_type) {
+							return Ok::<_, HyperError>(e);
 						}
 
-						// Closes the receiving half of a channel without dropping it. This prevents any further
-						// messages from being sent on the channel.
-						rx.close();
-						let response = if is_single {
-							rx.next().await.expect("Sender is still alive managed by us above; qed")
-						} else {
-							collect_batch_response(rx).await
-						};
-						tracing::debug!("[service_fn] sending back: {:?}", &response[..cmp::min(response.len(), 1024)]);
-						middleware.on_response(request_start);
-						Ok::<_, HyperError>(response::ok_response(response))
+						let cors_origin = access_control.allowed_cors_origin(&request);
+						let mut response = process_validated_request(
+							request,
+							methods,
+							resources,
+							middleware,
+							max_request_body_size,
+							max_batch_len,
+							batch_request_timeout,
+						)
+						.await?;
+						if let Some(origin) = cors_origin {
+							response.headers_mut().insert(hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+						}
+						Ok::<_, HyperError>(response)
 					}
 				}))
 			}
@@ -430,6 +500,147 @@ impl<M: Middleware> Server<M> {
 	}
 }
 
+/// Process a request that passed the HTTP level checks: read the body and execute the single RPC request or batch
+/// of RPC requests it contains.
+async fn process_validated_request<M: Middleware>(
+	request: hyper::Request<hyper::Body>,
+	methods: Methods,
+	resources: Resources,
+	middleware: M,
+	max_request_body_size: u32,
+	max_batch_len: usize,
+	batch_request_timeout: Option<Duration>,
+) -> Result<hyper::Response<hyper::Body>, HyperError> {
+	let (parts, body) = request.into_parts();
+
+	let (body, is_single) = match read_body(&parts.headers, body, max_request_body_size).await {
+		Ok(r) => r,
+		Err(GenericTransportError::TooLarge) => return Ok::<_, HyperError>(response::too_large()),
+		Err(GenericTransportError::Malformed) => return Ok::<_, HyperError>(response::malformed()),
+		Err(GenericTransportError::Inner(e)) => {
+			tracing::error!("Internal error reading request body: {}", e);
+			return Ok::<_, HyperError>(response::internal_error());
+		}
+	};
+
+	let request_start = middleware.on_request();
+
+	// NOTE(niklasad1): it's a channel because it's needed for batch requests.
+	let (tx, mut rx) = mpsc::unbounded::<String>();
+	let sink = MethodSink::new_with_limit(tx, max_request_body_size);
+
+	type Notif<'a> = Notification<'a, Option<&'a RawValue>>;
+
+	// Single request or notification
+	if is_single {
+		if let Ok(req) = serde_json::from_slice::<Request>(&body) {
+			middleware.on_call(req.method.as_ref());
+
+			// NOTE: we don't need to track connection id on HTTP, so using hardcoded 0 here.
+			match methods.execute_with_resources(&sink, req, 0, &resources) {
+				Ok((name, MethodResult::Sync(success))) => {
+					middleware.on_result(name, success, request_start);
+				}
+				Ok((name, MethodResult::Async(fut))) => {
+					let success = fut.await;
+
+					middleware.on_result(name, success, request_start);
+				}
+				Err(name) => {
+					middleware.on_result(name.as_ref(), false, request_start);
+				}
+			}
+		} else if let Ok(_req) = serde_json::from_slice::<Notif>(&body) {
+			return Ok::<_, HyperError>(response::ok_response("".into()));
+		} else {
+			let (id, code) = prepare_error(&body);
+			sink.send_error(id, code.into());
+		}
+
+	// Batch of requests or notifications
+	} else if let Ok(batch) = serde_json::from_slice::<Vec<&RawValue>>(&body) {
+		if batch.is_empty() {
+			// "If the batch rpc call itself fails to be recognized as an valid JSON or as an
+			// Array with at least one value, the response from the Server MUST be a single
+			// Response object." – The Spec.
+			sink.send_error(Id::Null, ErrorCode::InvalidRequest.into());
+		} else if batch.len() > max_batch_len {
+			let data = to_json_raw_value(&format!("Exceeded max batch limit {}", max_batch_len)).ok();
+			sink.send_error(Id::Null, ErrorObject::new(ErrorCode::InvalidRequest, data.as_deref()));
+		} else {
+			// Notifications in a batch are acknowledged but never answered.
+			let calls = split_batch(&batch);
+
+			if calls.is_empty() {
+				// "If there are no Response objects contained within the Response array as it is to
+				// be sent to the client, the server MUST NOT return an empty Array and should return
+				// nothing at all." – The Spec.
+				return Ok::<_, HyperError>(response::ok_response("".into()));
+			} else {
+				// NOTE: we don't need to track connection id on HTTP, so using hardcoded 0 here.
+				let response = execute_batch(
+					calls,
+					0,
+					&methods,
+					&resources,
+					&middleware,
+					request_start,
+					max_request_body_size,
+					batch_request_timeout,
+				)
+				.await;
+				if let Err(err) = sink.send_raw(response) {
+					tracing::error!("Error sending batch response to the client: {:?}", err);
+				}
+			}
+		}
+	} else {
+		// "If the batch rpc call itself fails to be recognized as an valid JSON or as an
+		// Array with at least one value, the response from the Server MUST be a single
+		// Response object." – The Spec.
+		let (id, code) = prepare_error(&body);
+		sink.send_error(id, code.into());
+	}
+
+	// Closes the receiving half of a channel without dropping it. This prevents any further
+	// messages from being sent on the channel.
+	rx.close();
+	let response = rx.next().await.expect("Sender is still alive managed by us above; qed");
+	tracing::debug!("[service_fn] sending back: {:?}", &response[..cmp::min(response.len(), 1024)]);
+	middleware.on_response(request_start);
+	Ok::<_, HyperError>(response::ok_response(response))
+}
+
+/// Call the method backing the health endpoint and answer with its result.
+async fn process_health_request(
+	health_api: &HealthApi,
+	methods: &Methods,
+	resources: &Resources,
+	max_response_size: u32,
+) -> hyper::Response<hyper::Body> {
+	let (tx, mut rx) = mpsc::unbounded::<String>();
+	let sink = MethodSink::new_with_limit(tx, max_response_size);
+	let req = Request::new(health_api.method.as_str().into(), None, Id::Number(0));
+
+	// NOTE: we don't need to track connection id on HTTP, so using hardcoded 0 here.
+	let success = match methods.execute_with_resources(&sink, req, 0, resources) {
+		Ok((_, MethodResult::Sync(success))) => success,
+		Ok((_, MethodResult::Async(fut))) => fut.await,
+		Err(_) => false,
+	};
+
+	rx.close();
+	let response = rx.next().await;
+	let result = response.as_deref().and_then(|response| serde_json::from_str::<Response<&RawValue>>(response).ok());
+	match result {
+		Some(result) if success => response::ok_response(result.result.get().to_owned()),
+		_ => {
+			tracing::warn!("Health method {} did not succeed: {:?}", health_api.method, response);
+			response::internal_error()
+		}
+	}
+}
+
 // Checks to that access control of the received request is the same as configured.
 fn access_control_is_valid(
 	access_control: &AccessControl,
@@ -447,24 +658,48 @@ fn access_control_is_valid(
 	Ok(())
 }
 
-/// Checks that content type of received request is valid for JSON-RPC.
-fn content_type_is_valid(request: &hyper::Request<hyper::Body>) -> Result<(), hyper::Response<hyper::Body>> {
-	match *request.method() {
-		hyper::Method::POST if is_json(request.headers().get("content-type")) => Ok(()),
-		_ => Err(response::method_not_allowed()),
+/// Answers a CORS preflight request, which has already passed the access control checks.
+fn cors_preflight_response(
+	access_control: &AccessControl,
+	request: &hyper::Request<hyper::Body>,
+) -> hyper::Response<hyper::Body> {
+	let mut response = hyper::Response::new(hyper::Body::empty());
+	let headers = response.headers_mut();
+	if let Some(origin) = access_control.allowed_cors_origin(request) {
+		headers.insert(hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+	}
+	headers.insert(hyper::header::ACCESS_CONTROL_ALLOW_METHODS, hyper::header::HeaderValue::from_static("POST"));
+	for header in access_control.allowed_cors_headers(request).into_iter().flatten() {
+		headers.append(hyper::header::ACCESS_CONTROL_ALLOW_HEADERS, header);
 	}
+	response
 }
 
-/// Returns true if the `content_type` header indicates a valid JSON message.
-fn is_json(content_type: Option<&hyper::header::HeaderValue>) -> bool {
-	match content_type.and_then(|val| val.to_str().ok()) {
-		Some(content)
-			if content.eq_ignore_ascii_case("application/json")
-				|| content.eq_ignore_ascii_case("application/json; charset=utf-8")
-				|| content.eq_ignore_ascii_case("application/json;charset=utf-8") =>
-		{
-			true
-		}
-		_ => false,
+/// Checks that the received request is a POST with a content type valid for JSON-RPC.
+fn content_type_is_valid(
+	request: &hyper::Request<hyper::Body>,
+	allow_missing_content_type: bool,
+) -> Result<(), hyper::Response<hyper::Body>> {
+	if request.method() != hyper::Method::POST {
+		return Err(response::method_not_allowed());
+	}
+	match request.headers().get(hyper::header::CONTENT_TYPE) {
+		None if allow_missing_content_type => Ok(()),
+		content_type if is_json(content_type) => Ok(()),
+		_ => Err(response::unsupported_content_type()),
 	}
 }
+
+/// Returns true if the `content_type` header indicates a valid JSON message, i.e. `application/json` or
+/// `application/json-rpc` with an optional UTF-8 charset.
+fn is_json(content_type: Option<&hyper::header::HeaderValue>) -> bool {
+	let content_type = match content_type.and_then(|val| val.to_str().ok()) {
+		Some(content_type) => content_type,
+		None => return false,
+	};
+	let mut parts = content_type.split(';').map(str::trim);
+	let mime = parts.next().unwrap_or_default();
+
+	(mime.eq_ignore_ascii_case("application/json") || mime.eq_ignore_ascii_case("application/json-rpc"))
+		&& parts.all(|param| param.eq_ignore_ascii_case("charset=utf-8"))
+}
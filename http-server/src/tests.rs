@@ -30,7 +30,7 @@ use std::net::SocketAddr;
 use std::time::Duration;
 
 use crate::types::error::{CallError, Error};
-use crate::{server::ServerHandle, HttpServerBuilder, RpcModule};
+use crate::{server::ServerHandle, AccessControlBuilder, HttpServerBuilder, RpcModule};
 
 use jsonrpsee_test_utils::helpers::*;
 use jsonrpsee_test_utils::mocks::{Id, StatusCode, TestContext};
@@ -58,6 +58,13 @@ async fn server() -> (SocketAddr, ServerHandle) {
 			Ok(r)
 		})
 		.unwrap();
+	module
+		.register_async_method("sleep_for_async", |params, _| async move {
+			let sleep: u64 = params.one()?;
+			tokio::time::sleep(Duration::from_millis(sleep)).await;
+			Ok(sleep)
+		})
+		.unwrap();
 	module.register_method("notif", |_, _| Ok("")).unwrap();
 	module
 		.register_method("should_err", |_, ctx| {
@@ -97,6 +104,22 @@ async fn single_method_call_works() {
 	}
 }
 
+#[tokio::test]
+async fn single_method_call_echoes_the_request_id() {
+	let _ = env_logger::try_init();
+	let (addr, _handle) = server().with_default_timeout().await.unwrap();
+	let uri = to_http_uri(addr);
+
+	let ids = [(r#"1337"#, Id::Num(1337)), (r#""foo""#, Id::Str("foo".into())), (r#"null"#, Id::Null)];
+
+	for (raw_id, id) in ids {
+		let req = format!(r#"{{"jsonrpc":"2.0","method":"say_hello","id":{}}}"#, raw_id);
+		let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+		assert_eq!(response.status, StatusCode::OK);
+		assert_eq!(response.body, ok_response(JsonValue::String("lo".to_owned()), id));
+	}
+}
+
 #[tokio::test]
 async fn async_method_call_works() {
 	let _ = env_logger::try_init();
@@ -211,6 +234,28 @@ async fn valid_batched_method_calls() {
 	);
 }
 
+#[tokio::test]
+async fn batched_method_calls_are_answered_in_request_order() {
+	let _ = env_logger::try_init();
+
+	let (addr, _handle) = server().with_default_timeout().await.unwrap();
+	let uri = to_http_uri(addr);
+
+	// The calls complete in the reverse order of how they were sent.
+	let req = r#"[
+		{"jsonrpc":"2.0","method":"sleep_for_async","params":[300],"id":1},
+		{"jsonrpc":"2.0","method":"sleep_for_async","params":[200],"id":2},
+		{"jsonrpc":"2.0","method":"sleep_for_async","params":[100],"id":3},
+		{"jsonrpc":"2.0","method":"say_hello","id":4}
+	]"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(
+		response.body,
+		r#"[{"jsonrpc":"2.0","result":300,"id":1},{"jsonrpc":"2.0","result":200,"id":2},{"jsonrpc":"2.0","result":100,"id":3},{"jsonrpc":"2.0","result":"lo","id":4}]"#
+	);
+}
+
 #[tokio::test]
 async fn batched_notifications() {
 	let _ = env_logger::try_init();
@@ -225,6 +270,44 @@ async fn batched_notifications() {
 	assert_eq!(response.body, "");
 }
 
+#[tokio::test]
+async fn batched_notifications_and_method_calls() {
+	let _ = env_logger::try_init();
+
+	let (addr, _handle) = server().with_default_timeout().await.unwrap();
+	let uri = to_http_uri(addr);
+
+	let req = r#"[{"jsonrpc": "2.0", "method": "notif", "params": [1,2,4]},{"jsonrpc": "2.0", "method": "say_hello", "id": 1}]"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(response.body, r#"[{"jsonrpc":"2.0","result":"lo","id":1}]"#);
+}
+
+#[tokio::test]
+async fn batched_method_calls_with_duplicate_ids() {
+	let _ = env_logger::try_init();
+
+	let (addr, _handle) = server().with_default_timeout().await.unwrap();
+	let uri = to_http_uri(addr);
+
+	let req = r#"[
+		{"jsonrpc":"2.0","method":"add", "params":[1, 2],"id":1},
+		{"jsonrpc":"2.0","method":"say_hello","id":2},
+		{"jsonrpc":"2.0","method":"add", "params":[3, 4],"id":1}
+	]"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(
+		response.body,
+		format!(
+			"[{},{},{}]",
+			invalid_request(Id::Num(1)),
+			r#"{"jsonrpc":"2.0","result":"lo","id":2}"#,
+			invalid_request(Id::Num(1))
+		)
+	);
+}
+
 #[tokio::test]
 async fn invalid_batched_method_calls() {
 	let _ = env_logger::try_init();
@@ -242,15 +325,16 @@ async fn invalid_batched_method_calls() {
 	let req = r#"[123]"#;
 	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
 	assert_eq!(response.status, StatusCode::OK);
-	// Note: according to the spec the `id` should be `null` here, not 123.
-	assert_eq!(response.body, invalid_request(Id::Num(123)));
+	assert_eq!(response.body, format!("[{}]", invalid_request(Id::Null)));
 
-	// batch with invalid request
+	// batch with invalid requests
 	let req = r#"[1, 2, 3]"#;
 	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
 	assert_eq!(response.status, StatusCode::OK);
-	// Note: according to the spec this should return an array of three `Invalid Request`s
-	assert_eq!(response.body, parse_error(Id::Null));
+	assert_eq!(
+		response.body,
+		format!("[{},{},{}]", invalid_request(Id::Null), invalid_request(Id::Null), invalid_request(Id::Null))
+	);
 
 	// invalid JSON in batch
 	let req = r#"[
@@ -262,6 +346,116 @@ async fn invalid_batched_method_calls() {
 	assert_eq!(response.body, parse_error(Id::Null));
 }
 
+#[tokio::test]
+async fn can_set_the_max_batch_len() {
+	let _ = env_logger::try_init();
+
+	let server = HttpServerBuilder::default().max_batch_len(2).build("127.0.0.1:0").unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _| Ok("lo")).unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let _handle = server.start(module).unwrap();
+
+	let req = r#"[{"jsonrpc":"2.0","method":"say_hello","id":1},{"jsonrpc":"2.0","method":"say_hello","id":2}]"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(response.body, r#"[{"jsonrpc":"2.0","result":"lo","id":1},{"jsonrpc":"2.0","result":"lo","id":2}]"#);
+
+	let req = r#"[
+		{"jsonrpc":"2.0","method":"say_hello","id":1},
+		{"jsonrpc":"2.0","method":"say_hello","id":2},
+		{"jsonrpc":"2.0","method":"say_hello","id":3}
+	]"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(response.body, batch_too_large(2));
+}
+
+#[tokio::test]
+async fn can_set_the_batch_request_timeout() {
+	let _ = env_logger::try_init();
+
+	let server =
+		HttpServerBuilder::default().batch_request_timeout(Duration::from_millis(100)).build("127.0.0.1:0").unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _| Ok("lo")).unwrap();
+	module
+		.register_async_method("sleep_for_async", |params, _| async move {
+			let sleep: u64 = params.one()?;
+			tokio::time::sleep(Duration::from_millis(sleep)).await;
+			Ok(sleep)
+		})
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let _handle = server.start(module).unwrap();
+
+	let req = r#"[
+		{"jsonrpc":"2.0","method":"sleep_for_async","params":[10000],"id":1},
+		{"jsonrpc":"2.0","method":"sleep_for_async","params":[10],"id":2},
+		{"jsonrpc":"2.0","method":"say_hello","id":3}
+	]"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(
+		response.body,
+		format!(
+			"[{},{},{}]",
+			internal_error(Id::Num(1)),
+			r#"{"jsonrpc":"2.0","result":10,"id":2}"#,
+			r#"{"jsonrpc":"2.0","result":"lo","id":3}"#
+		)
+	);
+}
+
+#[tokio::test]
+async fn batch_example_from_the_spec_works() {
+	let _ = env_logger::try_init();
+
+	let server = HttpServerBuilder::default().build("127.0.0.1:0").unwrap();
+	let mut module = RpcModule::new(());
+	module
+		.register_method("sum", |params, _| {
+			let params: Vec<u64> = params.parse()?;
+			Ok(params.into_iter().sum::<u64>())
+		})
+		.unwrap();
+	module
+		.register_method("subtract", |params, _| {
+			let (minuend, subtrahend): (i64, i64) = params.parse()?;
+			Ok(minuend - subtrahend)
+		})
+		.unwrap();
+	module.register_method("notify_hello", |_, _| Ok(())).unwrap();
+	module.register_method("get_data", |_, _| Ok(("hello", 5))).unwrap();
+	let addr = server.local_addr().unwrap();
+	let uri = to_http_uri(addr);
+	let _handle = server.start(module).unwrap();
+
+	let req = r#"[
+		{"jsonrpc": "2.0", "method": "sum", "params": [1,2,4], "id": "1"},
+		{"jsonrpc": "2.0", "method": "notify_hello", "params": [7]},
+		{"jsonrpc": "2.0", "method": "subtract", "params": [42,23], "id": "2"},
+		{"foo": "boo"},
+		{"jsonrpc": "2.0", "method": "foo.get", "params": {"name": "myself"}, "id": "5"},
+		{"jsonrpc": "2.0", "method": "get_data", "id": "9"}
+	]"#;
+	let response = http_request(req.into(), uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(
+		response.body,
+		format!(
+			"[{},{},{},{},{}]",
+			ok_response(7.into(), Id::Str("1".into())),
+			ok_response(19.into(), Id::Str("2".into())),
+			invalid_request(Id::Null),
+			method_not_found(Id::Str("5".into())),
+			ok_response(serde_json::json!(["hello", 5]), Id::Str("9".into())),
+		)
+	);
+}
+
 #[tokio::test]
 async fn garbage_request_fails() {
 	let (addr, _handle) = server().await;
@@ -378,6 +572,246 @@ async fn can_register_modules() {
 	assert_eq!(mod1.method_names().count(), 2);
 }
 
+#[tokio::test]
+async fn cors_preflight_and_simple_requests_work() {
+	let _ = env_logger::try_init();
+	let acl = AccessControlBuilder::new().set_allowed_origins(vec!["http://allowed.io"]).unwrap().build();
+	let server = HttpServerBuilder::default().set_access_control(acl).build("127.0.0.1:0").unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _| Ok("lo")).unwrap();
+	let _handle = server.start(module).unwrap();
+	let uri = to_http_uri(addr);
+	let client = hyper::Client::new();
+
+	let preflight = hyper::Request::options(uri.clone())
+		.header("origin", "http://allowed.io")
+		.header("access-control-request-method", "POST")
+		.header("access-control-request-headers", "content-type")
+		.body(hyper::Body::empty())
+		.unwrap();
+	let response = client.request(preflight).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	assert_eq!(response.headers()["access-control-allow-origin"], "http://allowed.io");
+	assert_eq!(response.headers()["access-control-allow-methods"], "POST");
+	assert_eq!(response.headers()["access-control-allow-headers"], "content-type");
+
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#;
+	let simple = |origin: &'static str| {
+		hyper::Request::post(uri.clone())
+			.header("origin", origin)
+			.header("content-type", "application/json")
+			.body(req.into())
+			.unwrap()
+	};
+	let response = client.request(simple("http://allowed.io")).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	assert_eq!(response.headers()["access-control-allow-origin"], "http://allowed.io");
+	let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+	assert_eq!(body, ok_response(JsonValue::String("lo".to_owned()), Id::Num(1)));
+
+	let response = client.request(simple("http://denied.io")).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+	assert!(response.headers().get("access-control-allow-origin").is_none());
+}
+
+async fn raw_request(
+	addr: SocketAddr,
+	method: hyper::Method,
+	content_type: Option<&'static str>,
+) -> hyper::Response<hyper::Body> {
+	let mut request = hyper::Request::builder().method(method).uri(to_http_uri(addr));
+	if let Some(content_type) = content_type {
+		request = request.header("content-type", content_type);
+	}
+	let request = request.body(r#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#.into()).unwrap();
+	hyper::Client::new().request(request).with_default_timeout().await.unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn http_method_and_content_type_are_checked() {
+	let _ = env_logger::try_init();
+	let (addr, _handle) = server().with_default_timeout().await.unwrap();
+
+	let response = raw_request(addr, hyper::Method::GET, Some("application/json")).await;
+	assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+	assert_eq!(response.headers()["allow"], "POST, OPTIONS");
+
+	let response = raw_request(addr, hyper::Method::POST, Some("text/plain")).await;
+	assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+	let response = raw_request(addr, hyper::Method::POST, None).await;
+	assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+	for content_type in ["application/json", "application/json-rpc", "application/json-rpc; charset=UTF-8"] {
+		let response = raw_request(addr, hyper::Method::POST, Some(content_type)).await;
+		assert_eq!(response.status(), StatusCode::OK);
+	}
+}
+
+#[tokio::test]
+async fn missing_content_type_can_be_allowed() {
+	let _ = env_logger::try_init();
+	let server = HttpServerBuilder::default().allow_missing_content_type(true).build("127.0.0.1:0").unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _| Ok("lo")).unwrap();
+	let _handle = server.start(module).unwrap();
+
+	let response = raw_request(addr, hyper::Method::POST, None).await;
+	assert_eq!(response.status(), StatusCode::OK);
+	let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+	assert_eq!(body, ok_response(JsonValue::String("lo".to_owned()), Id::Num(1)));
+
+	let response = raw_request(addr, hyper::Method::POST, Some("text/plain")).await;
+	assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn health_api_works() {
+	use std::sync::atomic::{AtomicBool, Ordering};
+	use std::sync::Arc;
+
+	let _ = env_logger::try_init();
+	let server = HttpServerBuilder::default().health_api("/health", "system_health").build("127.0.0.1:0").unwrap();
+	let addr = server.local_addr().unwrap();
+	let ready = Arc::new(AtomicBool::new(true));
+	let mut module = RpcModule::new(ready.clone());
+	module
+		.register_method("system_health", |_, ready| match ready.load(Ordering::SeqCst) {
+			true => Ok(serde_json::json!({ "syncing": false })),
+			false => Err(Error::Custom("still syncing".into())),
+		})
+		.unwrap();
+	let _handle = server.start(module).unwrap();
+	let uri: hyper::Uri = format!("http://{}/health", addr).parse().unwrap();
+	let client = hyper::Client::new();
+
+	let response = client.get(uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+	assert_eq!(body, r#"{"syncing":false}"#);
+
+	// The RPC endpoint is still served on the same path.
+	let req = r#"{"jsonrpc":"2.0","method":"system_health","id":1}"#;
+	let response = http_request(req.into(), uri.clone()).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(response.body, ok_response(serde_json::json!({ "syncing": false }), Id::Num(1)));
+
+	ready.store(false, Ordering::SeqCst);
+	let response = client.get(uri).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn build_from_tcp_works() {
+	let _ = env_logger::try_init();
+	let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+	let addr = listener.local_addr().unwrap();
+	let server = HttpServerBuilder::default().build_from_tcp(listener).unwrap();
+	assert_eq!(server.local_addr().unwrap(), addr);
+
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _| Ok("lo")).unwrap();
+	let handle = server.start(module).unwrap();
+
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#;
+	let response = http_request(req.into(), to_http_uri(addr)).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(response.body, ok_response(JsonValue::String("lo".to_owned()), Id::Num(1)));
+
+	handle.stop().unwrap().with_default_timeout().await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn build_from_tcp_applies_keep_alive() {
+	let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+	let socket = socket2::SockRef::from(&listener);
+	socket.set_keepalive(true).unwrap();
+	let same_listener = listener.try_clone().unwrap();
+
+	let _server = HttpServerBuilder::default().keep_alive(false).build_from_tcp(listener).unwrap();
+	assert!(!socket2::SockRef::from(&same_listener).keepalive().unwrap());
+}
+
+#[tokio::test]
+async fn http2_prior_knowledge_works() {
+	let _ = env_logger::try_init();
+	let (addr, _handle) = server().with_default_timeout().await.unwrap();
+	let client = hyper::Client::builder().http2_only(true).build_http::<hyper::Body>();
+
+	let calls = (0..10).map(|id| {
+		let req = format!(r#"{{"jsonrpc":"2.0","method":"say_hello","id":{}}}"#, id);
+		let request = hyper::Request::post(to_http_uri(addr))
+			.header("content-type", "application/json")
+			.body(req.into())
+			.unwrap();
+		client.request(request)
+	});
+
+	for (id, response) in
+		futures_util::future::join_all(calls).with_default_timeout().await.unwrap().into_iter().enumerate()
+	{
+		let response = response.unwrap();
+		assert_eq!(response.status(), StatusCode::OK);
+		assert_eq!(response.version(), hyper::Version::HTTP_2);
+		let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+		assert_eq!(body, ok_response(JsonValue::String("lo".to_owned()), Id::Num(id as u64)));
+	}
+}
+
+#[tokio::test]
+async fn http2_settings_can_be_configured() {
+	let _ = env_logger::try_init();
+	let server = HttpServerBuilder::default()
+		.http2_max_concurrent_streams(1)
+		.http2_initial_stream_window_size(1024 * 1024)
+		.http2_initial_connection_window_size(2 * 1024 * 1024)
+		.build("127.0.0.1:0")
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _| Ok("lo")).unwrap();
+	let _handle = server.start(module).unwrap();
+	let client = hyper::Client::builder().http2_only(true).build_http::<hyper::Body>();
+
+	// The calls reuse the same HTTP/2 connection, one stream at a time.
+	for id in 0..3 {
+		let req = format!(r#"{{"jsonrpc":"2.0","method":"say_hello","id":{}}}"#, id);
+		let request = hyper::Request::post(to_http_uri(addr))
+			.header("content-type", "application/json")
+			.body(req.into())
+			.unwrap();
+		let response = client.request(request).with_default_timeout().await.unwrap().unwrap();
+		assert_eq!(response.status(), StatusCode::OK);
+		assert_eq!(response.version(), hyper::Version::HTTP_2);
+		let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+		assert_eq!(body, ok_response(JsonValue::String("lo".to_owned()), Id::Num(id)));
+	}
+}
+
+#[tokio::test]
+async fn http1_request_without_host_header_is_rejected() {
+	use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+	let _ = env_logger::try_init();
+	let (addr, _handle) = server().with_default_timeout().await.unwrap();
+
+	// An absolute-form URI carries an authority, which must not stand in for the `Host` header on HTTP/1.1.
+	let body = r#"{"jsonrpc":"2.0","method":"say_hello","id":1}"#;
+	let request = format!(
+		"POST http://{}/ HTTP/1.1\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+		addr,
+		body.len(),
+		body
+	);
+	let mut socket = tokio::net::TcpStream::connect(addr).await.unwrap();
+	socket.write_all(request.as_bytes()).await.unwrap();
+	let mut response = String::new();
+	socket.read_to_string(&mut response).with_default_timeout().await.unwrap().unwrap();
+	assert!(response.starts_with("HTTP/1.1 403"), "unexpected response: {}", response);
+}
+
 #[tokio::test]
 async fn stop_works() {
 	let _ = env_logger::try_init();
@@ -385,6 +819,27 @@ async fn stop_works() {
 	assert!(matches!(server_handle.stop().unwrap().await, Ok(_)));
 }
 
+#[tokio::test]
+async fn stop_waits_for_in_flight_requests() {
+	let _ = env_logger::try_init();
+	let (addr, server_handle) = server().with_default_timeout().await.unwrap();
+	let uri = to_http_uri(addr);
+
+	let req = r#"{"jsonrpc":"2.0","method":"sleep_for_async","params":[500],"id":1}"#;
+	let in_flight = tokio::spawn(http_request(req.into(), uri.clone()));
+	tokio::time::sleep(Duration::from_millis(100)).await;
+
+	let stopped = server_handle.stop().unwrap();
+	let response = in_flight.with_default_timeout().await.unwrap().unwrap().unwrap();
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(response.body, ok_response(JsonValue::Number(500.into()), Id::Num(1)));
+	stopped.with_default_timeout().await.unwrap().unwrap();
+
+	// No new connections are accepted once stopped.
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":2}"#;
+	assert!(http_request(req.into(), uri).with_default_timeout().await.unwrap().is_err());
+}
+
 #[tokio::test]
 async fn run_forever() {
 	const TIMEOUT: Duration = Duration::from_millis(200);
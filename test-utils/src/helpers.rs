@@ -89,6 +89,13 @@ pub fn invalid_request(id: Id) -> String {
 	)
 }
 
+pub fn batch_too_large(max_limit: usize) -> String {
+	format!(
+		r#"{{"jsonrpc":"2.0","error":{{"code":-32600,"message":"Invalid request","data":"Exceeded max batch limit {}"}},"id":null}}"#,
+		max_limit,
+	)
+}
+
 pub fn invalid_params(id: Id) -> String {
 	format!(
 		r#"{{"jsonrpc":"2.0","error":{{"code":-32602,"message":"Invalid params"}},"id":{}}}"#,
@@ -53,6 +53,8 @@ struct CounterInner {
 	requests: (u32, u32),
 	/// Mapping method names to (number of calls, ids of successfully completed calls)
 	calls: HashMap<String, (u32, Vec<u32>)>,
+	/// Mapping method names to ids of failed calls
+	failures: HashMap<String, Vec<u32>>,
 }
 
 impl Middleware for Counter {
@@ -80,8 +82,12 @@ impl Middleware for Counter {
 	}
 
 	fn on_result(&self, name: &str, success: bool, n: u32) {
+		let mut inner = self.inner.lock().unwrap();
+
 		if success {
-			self.inner.lock().unwrap().calls.get_mut(name).unwrap().1.push(n);
+			inner.calls.get_mut(name).unwrap().1.push(n);
+		} else {
+			inner.failures.entry(name.into()).or_default().push(n);
 		}
 	}
 
@@ -195,3 +201,38 @@ async fn http_server_middleware() {
 	// HTTP server doesn't track connections
 	assert_eq!(inner.connections, (0, 0));
 }
+
+#[tokio::test]
+async fn http_server_middleware_reports_timed_out_batch_calls() {
+	let counter = Counter::default();
+	let mut module = RpcModule::new(());
+	module.register_method("fast", |_, _| Ok("fast")).unwrap();
+	module
+		.register_async_method("slow", |_, _| async {
+			sleep(Duration::from_secs(1)).await;
+			Ok("slow")
+		})
+		.unwrap();
+
+	let server = HttpServerBuilder::default()
+		.batch_request_timeout(Duration::from_millis(100))
+		.set_middleware(counter.clone())
+		.build("127.0.0.1:0")
+		.unwrap();
+	let server_url = format!("http://{}", server.local_addr().unwrap());
+	let server_handle = server.start(module).unwrap();
+	let client = HttpClientBuilder::default().build(&server_url).unwrap();
+
+	assert!(client.batch_request::<String>(vec![("fast", None), ("slow", None)]).await.is_err());
+
+	{
+		let inner = counter.inner.lock().unwrap();
+
+		assert_eq!(inner.requests, (1, 1));
+		assert_eq!(inner.calls["fast"], (1, vec![0]));
+		assert_eq!(inner.calls["slow"], (1, vec![]));
+		assert_eq!(inner.failures["slow"], vec![0]);
+	}
+
+	server_handle.stop().unwrap().await.unwrap();
+}
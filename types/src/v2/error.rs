@@ -304,4 +304,20 @@ mod tests {
 		let ser = serde_json::to_string(&err).unwrap();
 		assert_eq!(exp, ser);
 	}
+
+	#[test]
+	fn serialize_with_optional_data_works() {
+		let exp = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params","data":{"field":"name","expected":"string"}},"id":1337}"#;
+		let data = serde_json::value::RawValue::from_string(r#"{"field":"name","expected":"string"}"#.into()).unwrap();
+		let err = RpcError {
+			jsonrpc: TwoPointZero,
+			error: ErrorObject { code: ErrorCode::InvalidParams, message: "Invalid params".into(), data: Some(&*data) },
+			id: Id::Number(1337),
+		};
+		let ser = serde_json::to_string(&err).unwrap();
+		assert_eq!(exp, ser);
+
+		let de: RpcError = serde_json::from_str(&ser).unwrap();
+		assert_eq!(de, err);
+	}
 }
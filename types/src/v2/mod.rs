@@ -36,6 +36,6 @@ pub mod request;
 pub mod response;
 
 pub use error::{ErrorCode, ErrorObject, RpcError};
-pub use params::{Id, Params, ParamsSequence, ParamsSer, SubscriptionId, TwoPointZero};
+pub use params::{deserialize_if_present, Id, Params, ParamsSequence, ParamsSer, SubscriptionId, TwoPointZero};
 pub use request::{InvalidRequest, Notification, NotificationSer, Request, RequestSer};
 pub use response::{Response, SubscriptionPayload, SubscriptionResponse};
@@ -249,6 +249,59 @@ impl<'a> ParamsSequence<'a> {
 			None => Ok(None),
 		}
 	}
+
+	/// Parse the next parameter to type `T` if it is present in the supplied JSON array.
+	///
+	/// Unlike [`ParamsSequence::optional_next`] the result is only `None` for missing values, an explicit `null`
+	/// is deserialized into `T` which makes it possible to tell the two apart.
+	///
+	/// ```
+	/// # use jsonrpsee_types::v2::params::Params;
+	/// let params = Params::new(Some(r#"[1, null]"#));
+	/// let mut seq = params.sequence();
+	///
+	/// let params: [Option<Option<u32>>; 3] = [
+	///     seq.next_if_present().unwrap(),
+	///     seq.next_if_present().unwrap(),
+	///     seq.next_if_present().unwrap(),
+	/// ];
+	///
+	/// assert_eq!(params, [Some(Some(1)), Some(None), None]);
+	/// ```
+	pub fn next_if_present<T>(&mut self) -> Result<Option<T>, CallError>
+	where
+		T: Deserialize<'a>,
+	{
+		match self.next_inner::<T>() {
+			Some(result) => result.map(Some),
+			None => Ok(None),
+		}
+	}
+}
+
+/// Deserialize a named parameter so that a missing field and an explicit `null` can be told apart, the named
+/// params counterpart of [`ParamsSequence::next_if_present`].
+///
+/// Use it on an `Option<Option<T>>` field together with `#[serde(default)]`: a missing field is `None`, `null`
+/// is `Some(None)` and any other value is `Some(Some(value))`.
+///
+/// ```
+/// # use jsonrpsee_types::v2::params::{deserialize_if_present, Params};
+/// #[derive(serde::Deserialize)]
+/// struct Update {
+///     #[serde(default, deserialize_with = "deserialize_if_present")]
+///     label: Option<Option<String>>,
+/// }
+///
+/// let update: Update = Params::new(Some(r#"{"label":null}"#)).parse().unwrap();
+/// assert_eq!(update.label, Some(None));
+/// ```
+pub fn deserialize_if_present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+	D: Deserializer<'de>,
+	T: Deserialize<'de>,
+{
+	Option::<T>::deserialize(deserializer).map(Some)
 }
 
 /// [Serializable JSON-RPC parameters](https://www.jsonrpc.org/specification#parameter_structures)
@@ -389,8 +442,10 @@ impl<'a> Id<'a> {
 
 #[cfg(test)]
 mod test {
-	use super::{Cow, Id, JsonValue, Params, ParamsSer, SubscriptionId, TwoPointZero};
+	use super::{deserialize_if_present, Cow, Id, JsonValue, Params, ParamsSer, SubscriptionId, TwoPointZero};
 	use crate::v2::response::SubscriptionPayload;
+	use crate::CallError;
+	use serde::Deserialize;
 
 	#[test]
 	fn id_deserialization() {
@@ -482,6 +537,66 @@ mod test {
 		assert!(obj.is_ok());
 	}
 
+	#[test]
+	fn params_parse_into_typed_values() {
+		#[derive(Deserialize, Debug, PartialEq)]
+		struct Named<'a> {
+			name: &'a str,
+			count: u64,
+		}
+
+		let positional = Params::new(Some(r#"["foo", 7]"#));
+		let tuple: (String, u64) = positional.parse().unwrap();
+		assert_eq!(tuple, ("foo".to_string(), 7));
+
+		let named = Params::new(Some(r#"{"count":7,"name":"foo"}"#));
+		let obj: Named = named.parse().unwrap();
+		assert_eq!(obj, Named { name: "foo", count: 7 });
+
+		let missing_field = Params::new(Some(r#"{"name":"foo"}"#));
+		match missing_field.parse::<Named>() {
+			Err(CallError::InvalidParams(e)) => assert!(e.to_string().contains("missing field `count`")),
+			other => panic!("Expected InvalidParams, got: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn params_sequence_distinguishes_missing_from_null() {
+		let params = Params::new(Some("[null, 3]"));
+		let mut seq = params.sequence();
+
+		assert_eq!(seq.next_if_present::<Option<u64>>().unwrap(), Some(None));
+		assert_eq!(seq.next_if_present::<Option<u64>>().unwrap(), Some(Some(3)));
+		assert_eq!(seq.next_if_present::<Option<u64>>().unwrap(), None);
+
+		let params = Params::new(Some(r#"["foo"]"#));
+		assert!(matches!(params.sequence().next_if_present::<u64>(), Err(CallError::InvalidParams(_))));
+
+		let no_params = Params::new(None);
+		assert_eq!(no_params.sequence().next_if_present::<Option<u64>>().unwrap(), None);
+	}
+
+	#[test]
+	fn named_params_distinguish_missing_from_null() {
+		#[derive(Deserialize, Debug, PartialEq)]
+		struct Named {
+			#[serde(default, deserialize_with = "deserialize_if_present")]
+			count: Option<Option<u64>>,
+		}
+
+		let absent: Named = Params::new(Some("{}")).parse().unwrap();
+		assert_eq!(absent, Named { count: None });
+
+		let null: Named = Params::new(Some(r#"{"count":null}"#)).parse().unwrap();
+		assert_eq!(null, Named { count: Some(None) });
+
+		let present: Named = Params::new(Some(r#"{"count":3}"#)).parse().unwrap();
+		assert_eq!(present, Named { count: Some(Some(3)) });
+
+		let invalid = Params::new(Some(r#"{"count":"foo"}"#));
+		assert!(matches!(invalid.parse::<Named>(), Err(CallError::InvalidParams(_))));
+	}
+
 	#[test]
 	fn params_sequence_borrows() {
 		let params = Params::new(Some(r#"["foo", "bar"]"#));
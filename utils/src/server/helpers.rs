@@ -24,18 +24,26 @@
 // IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
+use crate::server::resource_limiting::Resources;
+use crate::server::rpc_module::{ConnectionId, MethodResult, Methods};
 use futures_channel::mpsc;
+use futures_util::future::join_all;
 use futures_util::stream::StreamExt;
 use jsonrpsee_types::error::{CallError, Error};
+use jsonrpsee_types::middleware::Middleware;
 use jsonrpsee_types::to_json_raw_value;
 use jsonrpsee_types::v2::error::{OVERSIZED_RESPONSE_CODE, OVERSIZED_RESPONSE_MSG};
 use jsonrpsee_types::v2::{
 	error::{CALL_EXECUTION_FAILED_CODE, UNKNOWN_ERROR_CODE},
-	ErrorCode, ErrorObject, Id, InvalidRequest, Response, RpcError,
+	ErrorCode, ErrorObject, Id, InvalidRequest, Notification, Request, Response, RpcError,
 };
+use rustc_hash::FxHashSet;
 use serde::Serialize;
+use serde_json::value::RawValue;
 
 use std::io;
+use std::time::Duration;
+use tokio::time::{timeout_at, Instant};
 
 /// Bounded writer that allows writing at most `max_len` bytes.
 ///
@@ -101,6 +109,11 @@ impl MethodSink {
 		MethodSink { tx, max_response_size }
 	}
 
+	/// Returns whether the receiving end of the sink, i.e. the connection, has gone away.
+	pub fn is_closed(&self) -> bool {
+		self.tx.is_closed()
+	}
+
 	/// Send a JSON-RPC response to the client. If the serialization of `result` exceeds `max_response_size`,
 	/// an error will be sent instead.
 	pub fn send_response(&self, id: Id, result: impl Serialize) -> bool {
@@ -193,27 +206,130 @@ pub fn prepare_error(data: &[u8]) -> (Id<'_>, ErrorCode) {
 	}
 }
 
-/// Read all the results of all method calls in a batch request from the ['Stream']. Format the result into a single
-/// `String` appropriately wrapped in `[`/`]`.
-pub async fn collect_batch_response(rx: mpsc::UnboundedReceiver<String>) -> String {
+/// Split the elements of a batch request into method calls and invalid requests, the latter carrying the [`Id`] to
+/// answer them with (`null` if none could be found). Notifications are left out as they are never answered.
+pub fn split_batch<'a>(batch: &[&'a RawValue]) -> Vec<Result<Request<'a>, Id<'a>>> {
+	let mut calls = Vec::with_capacity(batch.len());
+	for raw in batch {
+		let raw = raw.get();
+		if let Ok(req) = serde_json::from_str::<Request>(raw) {
+			calls.push(Ok(req));
+		} else if serde_json::from_str::<Notification<Option<&RawValue>>>(raw).is_err() {
+			let id = serde_json::from_str::<InvalidRequest>(raw).map_or(Id::Null, |req| req.id);
+			calls.push(Err(id));
+		}
+	}
+	calls
+}
+
+/// Find the ids that are used by more than one call in a batch request. Calls sharing an id can't be told apart
+/// by the client once answered, so they are rejected. `null` ids are not considered.
+pub fn duplicate_ids<'a, 'b: 'a>(calls: impl IntoIterator<Item = &'a Request<'b>>) -> FxHashSet<Id<'b>> {
+	let mut seen = FxHashSet::default();
+	let mut duplicates = FxHashSet::default();
+	for call in calls {
+		if call.id != Id::Null && !seen.insert(&call.id) {
+			duplicates.insert(call.id.clone());
+		}
+	}
+	duplicates
+}
+
+/// Format the results of all method calls in a batch request into a single `String` appropriately wrapped in
+/// `[`/`]`. The responses are written in the order they are yielded, so callers are expected to pass them in the
+/// same order as the calls appeared in the batch.
+pub fn collect_batch_response(responses: impl IntoIterator<Item = String>) -> String {
 	let mut buf = String::with_capacity(2048);
 	buf.push('[');
-	let mut buf = rx
-		.fold(buf, |mut acc, response| async move {
-			acc.push_str(&response);
-			acc.push(',');
-			acc
-		})
-		.await;
+	for response in responses {
+		buf.push_str(&response);
+		buf.push(',');
+	}
 	// Remove trailing comma
-	buf.pop();
+	if buf.ends_with(',') {
+		buf.pop();
+	}
 	buf.push(']');
 	buf
 }
 
+/// Execute the calls of a batch request and collect their responses, in the same order as the calls were sent,
+/// into a single batch response. Invalid requests in the batch are answered with `Invalid request`.
+#[allow(clippy::too_many_arguments)]
+pub async fn execute_batch<M: Middleware>(
+	calls: Vec<Result<Request<'_>, Id<'_>>>,
+	conn_id: ConnectionId,
+	methods: &Methods,
+	resources: &Resources,
+	middleware: &M,
+	request_start: M::Instant,
+	max_response_size: u32,
+	batch_request_timeout: Option<Duration>,
+) -> String {
+	// Calls sharing the same id are answered with `Invalid request` instead of being executed.
+	let duplicates = &duplicate_ids(calls.iter().filter_map(|call| call.as_ref().ok()));
+	let deadline = batch_request_timeout.map(|timeout| Instant::now() + timeout);
+
+	// Each call gets its own channel so that the responses can be put back together in order, regardless of the
+	// order in which the calls complete.
+	let responses = join_all(calls.into_iter().map(move |call| {
+		let (tx, mut rx) = mpsc::unbounded();
+		let sink = MethodSink::new_with_limit(tx, max_response_size);
+
+		let fut = match call {
+			Err(id) => {
+				sink.send_error(id, ErrorCode::InvalidRequest.into());
+				None
+			}
+			Ok(req) if duplicates.contains(&req.id) => {
+				sink.send_error(req.id, ErrorCode::InvalidRequest.into());
+				None
+			}
+			Ok(req) => {
+				let id = req.id.clone();
+				middleware.on_call(req.method.as_ref());
+				match methods.execute_with_resources(&sink, req, conn_id, resources) {
+					Ok((name, MethodResult::Sync(success))) => {
+						middleware.on_result(name, success, request_start);
+						None
+					}
+					Ok((name, MethodResult::Async(fut))) => Some((id, name, fut)),
+					Err(name) => {
+						middleware.on_result(name.as_ref(), false, request_start);
+						None
+					}
+				}
+			}
+		};
+
+		async move {
+			match (fut, deadline) {
+				(Some((id, name, fut)), Some(deadline)) => match timeout_at(deadline, fut).await {
+					Ok(success) => middleware.on_result(name, success, request_start),
+					Err(_) => {
+						tracing::warn!("Batch request timed out before call with id={:?} completed", id);
+						sink.send_error(id, ErrorCode::InternalError.into());
+						middleware.on_result(name, false, request_start);
+					}
+				},
+				(Some((_, name, fut)), None) => {
+					let success = fut.await;
+					middleware.on_result(name, success, request_start);
+				}
+				(None, _) => (),
+			}
+			rx.close();
+			rx.next().await
+		}
+	}))
+	.await;
+
+	collect_batch_response(responses.into_iter().flatten())
+}
+
 #[cfg(test)]
 mod tests {
-	use super::{BoundedWriter, Id, Response};
+	use super::{duplicate_ids, split_batch, BoundedWriter, Id, RawValue, Request, Response};
 
 	#[test]
 	fn bounded_serializer_work() {
@@ -230,4 +346,38 @@ mod tests {
 		// NOTE: `"` is part of the serialization so 101 characters.
 		assert!(serde_json::to_writer(&mut writer, &"x".repeat(99)).is_err());
 	}
+
+	#[test]
+	fn duplicate_ids_works() {
+		let batch = r#"[
+			{"jsonrpc":"2.0","method":"a","id":1},
+			{"jsonrpc":"2.0","method":"b","id":"1"},
+			{"jsonrpc":"2.0","method":"c","id":1},
+			{"jsonrpc":"2.0","method":"d","id":null},
+			{"jsonrpc":"2.0","method":"e","id":null},
+			{"jsonrpc":"2.0","method":"f","id":2}
+		]"#;
+		let calls: Vec<Request> = serde_json::from_str(batch).unwrap();
+		let duplicates = duplicate_ids(&calls);
+		assert_eq!(duplicates.len(), 1);
+		assert!(duplicates.contains(&Id::Number(1)));
+	}
+
+	#[test]
+	fn split_batch_works() {
+		let batch = r#"[
+			{"jsonrpc":"2.0","method":"a","id":1},
+			{"jsonrpc":"2.0","method":"b","params":[7]},
+			{"foo":"boo"},
+			{"jsonrpc":"2.0","method":1,"id":"2"},
+			1
+		]"#;
+		let batch: Vec<&RawValue> = serde_json::from_str(batch).unwrap();
+		let calls = split_batch(&batch);
+		assert_eq!(calls.len(), 4);
+		assert!(matches!(&calls[0], Ok(req) if req.method == "a" && req.id == Id::Number(1)));
+		assert_eq!(calls[1].as_ref().unwrap_err(), &Id::Null);
+		assert_eq!(calls[2].as_ref().unwrap_err(), &Id::Str("2".into()));
+		assert_eq!(calls[3].as_ref().unwrap_err(), &Id::Null);
+	}
 }
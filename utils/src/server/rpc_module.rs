@@ -35,8 +35,8 @@ use jsonrpsee_types::{
 	error::{Error, SubscriptionClosedError},
 	traits::ToRpcParams,
 	v2::{
-		ErrorCode, Id, Params, Request, Response, SubscriptionId as RpcSubscriptionId, SubscriptionPayload,
-		SubscriptionResponse,
+		ErrorCode, ErrorObject, Id, Params, Request, Response, SubscriptionId as RpcSubscriptionId,
+		SubscriptionPayload, SubscriptionResponse,
 	},
 	DeserializeOwned,
 };
@@ -48,6 +48,8 @@ use std::collections::hash_map::Entry;
 use std::fmt::{self, Debug};
 use std::future::Future;
 use std::ops::{Deref, DerefMut};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 /// A `MethodCallback` is an RPC endpoint, callable with a standard JSON-RPC request,
@@ -177,7 +179,11 @@ impl MethodCallback {
 					conn_id
 				);
 
-				let result = (callback)(id, params, sink, conn_id);
+				let call = AssertUnwindSafe(|| (callback)(id.clone(), params, sink, conn_id));
+				let result = match panic::catch_unwind(call) {
+					Ok(result) => result,
+					Err(_) => send_panic_error(sink, id),
+				};
 
 				// Release claimed resources
 				drop(claimed);
@@ -195,7 +201,16 @@ impl MethodCallback {
 					conn_id
 				);
 
-				MethodResult::Async((callback)(id, params, sink, claimed))
+				let future = (callback)(id.clone(), params, sink.clone(), claimed);
+				MethodResult::Async(
+					AssertUnwindSafe(future)
+						.catch_unwind()
+						.map(move |result| match result {
+							Ok(result) => result,
+							Err(_) => send_panic_error(&sink, id),
+						})
+						.boxed(),
+				)
 			}
 		};
 
@@ -203,6 +218,13 @@ impl MethodCallback {
 	}
 }
 
+/// Answer a call whose callback panicked with an `Internal error`, so the client isn't left waiting.
+fn send_panic_error(sink: &MethodSink, id: Id) -> bool {
+	tracing::error!("[MethodCallback::execute] Callback panicked, req.id={:?}", id);
+	sink.send_error(id, ErrorCode::InternalError.into());
+	false
+}
+
 impl Debug for MethodKind {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
@@ -596,6 +618,8 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 				})
 				.map(|result| match result {
 					Ok(r) => r,
+					// Let the panic be handled like the ones of any other method.
+					Err(err) if err.is_panic() => panic::resume_unwind(err.into_panic()),
 					Err(err) => {
 						tracing::error!("Join error for blocking RPC method: {:?}", err);
 						false
@@ -625,6 +649,14 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 	///     - [`SubscriptionSink`]: A sink to send messages to the subscriber.
 	///     - Context: Any type that can be embedded into the [`RpcModule`].
 	///
+	/// The subscription is confirmed to the client once the sink is used or the callback returns `Ok`, whatever
+	/// comes first. Use [`SubscriptionSink::reject`] to answer the subscribe call with an error instead; returning
+	/// `Err` before the subscription was confirmed answers it with a generic server error, and dropping the sink
+	/// without deciding answers it with an internal error.
+	///
+	/// The decision has to be made before the callback returns: once it returned `Ok` the subscription is confirmed,
+	/// so a check that only completes later (e.g. in a spawned task) can close the subscription but not reject it.
+	///
 	/// # Examples
 	///
 	/// ```no_run
@@ -668,35 +700,64 @@ impl<Context: Send + Sync + 'static> RpcModule<Context> {
 				subscribe_method_name,
 				MethodCallback::new_sync(Arc::new(move |id, params, method_sink, conn_id| {
 					let (conn_tx, conn_rx) = oneshot::channel::<()>();
-					let sub_id = {
-						const JS_NUM_MASK: SubscriptionId = !0 >> 11;
-						let sub_id = rand::random::<SubscriptionId>() & JS_NUM_MASK;
-						let uniq_sub = SubscriptionKey { conn_id, sub_id };
-
-						subscribers.lock().insert(uniq_sub, (method_sink.clone(), conn_rx));
-
-						sub_id
-					};
-
-					method_sink.send_response(id.clone(), sub_id);
+					let pending = Arc::new(Mutex::new(Some(id.clone().into_owned())));
+					let sub_id = next_subscription_id();
+					let uniq_sub = SubscriptionKey { conn_id, sub_id };
+					subscribers.lock().insert(uniq_sub, (method_sink.clone(), conn_rx));
 
 					let sink = SubscriptionSink {
 						inner: method_sink.clone(),
 						method: notif_method_name,
 						subscribers: subscribers.clone(),
-						uniq_sub: SubscriptionKey { conn_id, sub_id },
+						uniq_sub,
+						pending: pending.clone(),
 						is_connected: Some(conn_tx),
 					};
-					if let Err(err) = callback(params, sink, ctx.clone()) {
-						tracing::error!(
-							"subscribe call '{}' failed: {:?}, request id={:?}",
-							subscribe_method_name,
-							err,
-							id
-						);
-						method_sink.send_error(id, ErrorCode::ServerError(CALL_EXECUTION_FAILED_CODE).into())
-					} else {
-						true
+					// Catch panics here, the subscribe call must not be answered again if it was already accepted.
+					let result = panic::catch_unwind(AssertUnwindSafe(|| callback(params, sink, ctx.clone())));
+
+					// Answer the subscribe call unless the callback already accepted or rejected the subscription.
+					let mut pending = pending.lock();
+					let result = match result {
+						Ok(result) => result,
+						Err(_) => match pending.take() {
+							Some(id) => return send_panic_error(method_sink, id),
+							None => {
+								tracing::error!(
+									"subscribe call '{}' panicked after answering, request id={:?}",
+									subscribe_method_name,
+									id
+								);
+								return false;
+							}
+						},
+					};
+					match (result, pending.take()) {
+						// The sink was dropped without accepting or rejecting the subscription.
+						(Ok(()), Some(id)) if !subscribers.lock().contains_key(&uniq_sub) => {
+							tracing::error!(
+								"subscribe call '{}' dropped the sink, request id={:?}",
+								subscribe_method_name,
+								id
+							);
+							method_sink.send_error(id, ErrorCode::InternalError.into());
+							false
+						}
+						(Ok(()), Some(id)) => method_sink.send_response(id, sub_id),
+						(Ok(()), None) => true,
+						(Err(err), pending_id) => {
+							tracing::error!(
+								"subscribe call '{}' failed: {:?}, request id={:?}",
+								subscribe_method_name,
+								err,
+								id
+							);
+							if let Some(id) = pending_id {
+								subscribers.lock().remove(&uniq_sub);
+								method_sink.send_error(id, ErrorCode::ServerError(CALL_EXECUTION_FAILED_CODE).into());
+							}
+							false
+						}
 					}
 				})),
 			);
@@ -759,6 +820,8 @@ pub struct SubscriptionSink {
 	uniq_sub: SubscriptionKey,
 	/// Shared Mutex of subscriptions for this method.
 	subscribers: Subscribers,
+	/// ID of the subscribe call until the subscription has been accepted or rejected.
+	pending: Arc<Mutex<Option<Id<'static>>>>,
 	/// A type to track whether the subscription is active (the subscriber is connected).
 	///
 	/// None - implies that the subscription as been closed.
@@ -766,6 +829,35 @@ pub struct SubscriptionSink {
 }
 
 impl SubscriptionSink {
+	/// Accept the subscription, i.e. answer the subscribe call with the subscription ID.
+	///
+	/// This is done automatically by the first [`SubscriptionSink::send`] or once the subscription callback
+	/// returns `Ok`, so it's only needed to confirm the subscription before that. Returns `false` if the
+	/// subscription was already accepted or rejected.
+	pub fn accept(&mut self) -> bool {
+		// The lock is held while sending to not let any notification overtake the confirmation.
+		let mut pending = self.pending.lock();
+		match pending.take() {
+			Some(id) => self.inner.send_response(id, self.uniq_sub.sub_id),
+			None => false,
+		}
+	}
+
+	/// Reject the subscription by answering the subscribe call with `err` instead of the subscription ID.
+	///
+	/// If the subscription was already accepted it's closed instead and `false` is returned.
+	pub fn reject(mut self, err: ErrorObject) -> bool {
+		let pending = self.pending.lock().take();
+		match pending {
+			Some(id) => {
+				self.is_connected.take();
+				self.subscribers.lock().remove(&self.uniq_sub);
+				self.inner.send_error(id, err)
+			}
+			None => false,
+		}
+	}
+
 	/// Send a message back to subscribers.
 	pub fn send<T: Serialize>(&mut self, result: &T) -> Result<(), Error> {
 		let msg = self.build_message(result)?;
@@ -773,14 +865,12 @@ impl SubscriptionSink {
 	}
 
 	fn build_message<T: Serialize>(&self, result: &T) -> Result<String, Error> {
-		serde_json::to_string(&SubscriptionResponse::new(
-			self.method.into(),
-			SubscriptionPayload { subscription: RpcSubscriptionId::Num(self.uniq_sub.sub_id), result },
-		))
-		.map_err(Into::into)
+		build_subscription_message(self.method, self.uniq_sub.sub_id, result)
 	}
 
 	fn inner_send(&mut self, msg: String) -> Result<(), Error> {
+		self.accept();
+
 		let res = match self.is_connected.as_ref() {
 			Some(conn) if !conn.is_canceled() => {
 				// unbounded send only fails if the receiver has been dropped.
@@ -803,6 +893,12 @@ impl SubscriptionSink {
 		})
 	}
 
+	/// Returns whether the subscription is closed, either by an unsubscribe call, by the server or because the
+	/// client disconnected. Sending to a closed subscription fails.
+	pub fn is_closed(&self) -> bool {
+		self.inner.is_closed() || self.is_connected.as_ref().map_or(true, |conn| conn.is_canceled())
+	}
+
 	/// Close the subscription sink with a customized error message.
 	pub fn close(&mut self, msg: &str) {
 		let err = SubscriptionClosedError::new(msg, self.uniq_sub.sub_id);
@@ -810,6 +906,8 @@ impl SubscriptionSink {
 	}
 
 	fn inner_close(&mut self, err: &SubscriptionClosedError) {
+		// A subscription that is closed before it was accepted is first confirmed so the client is not left waiting.
+		self.accept();
 		self.is_connected.take();
 		if let Some((sink, _)) = self.subscribers.lock().remove(&self.uniq_sub) {
 			tracing::debug!("Closing subscription: {:?}", self.uniq_sub.sub_id);
@@ -821,11 +919,37 @@ impl SubscriptionSink {
 
 impl Drop for SubscriptionSink {
 	fn drop(&mut self) {
-		let err = SubscriptionClosedError::new("Closed by the server", self.uniq_sub.sub_id);
-		self.inner_close(&err);
+		let pending = self.pending.clone();
+		let pending = pending.lock();
+		if pending.is_some() {
+			// Not accepted yet, the subscribe call is answered once the subscription callback returns.
+			self.subscribers.lock().remove(&self.uniq_sub);
+		} else {
+			drop(pending);
+			let err = SubscriptionClosedError::new("Closed by the server", self.uniq_sub.sub_id);
+			self.inner_close(&err);
+		}
 	}
 }
 
+/// Source of subscription IDs, shared by all subscriptions of all connections.
+static NEXT_SUBSCRIPTION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Mint a new subscription ID. IDs are handed out in increasing order, so an ID is never reused on a connection, not
+/// even after the subscription it was minted for is gone. They're masked to 53 bits to be exact as JavaScript numbers.
+fn next_subscription_id() -> SubscriptionId {
+	const JS_NUM_MASK: SubscriptionId = !0 >> 11;
+	NEXT_SUBSCRIPTION_ID.fetch_add(1, Ordering::Relaxed) & JS_NUM_MASK
+}
+
+fn build_subscription_message<T: Serialize>(method: &str, sub_id: SubscriptionId, result: &T) -> Result<String, Error> {
+	serde_json::to_string(&SubscriptionResponse::new(
+		method.into(),
+		SubscriptionPayload { subscription: RpcSubscriptionId::Num(sub_id), result },
+	))
+	.map_err(Into::into)
+}
+
 /// Wrapper struct that maintains a subscription "mainly" for testing.
 #[derive(Debug)]
 pub struct Subscription {
@@ -953,6 +1077,65 @@ mod tests {
 		assert_eq!(res, 25);
 	}
 
+	#[tokio::test]
+	async fn pre_serialized_result_is_sent_verbatim() {
+		let mut module = RpcModule::new(());
+		module
+			.register_method("cached_state", |_, _| {
+				serde_json::value::RawValue::from_string(r#"{ "b": [1, 2], "a": null }"#.into()).map_err(Into::into)
+			})
+			.unwrap();
+
+		let (resp, _) = module.raw_json_request(r#"{"jsonrpc":"2.0","method":"cached_state","id":7}"#).await.unwrap();
+		assert_eq!(resp, r#"{"jsonrpc":"2.0","result":{ "b": [1, 2], "a": null },"id":7}"#);
+	}
+
+	#[tokio::test]
+	async fn panicking_method_is_answered_with_internal_error() {
+		let mut module = RpcModule::new(());
+		module.register_method::<(), _>("sync_panic", |_, _| panic!("sync")).unwrap();
+		module.register_async_method::<(), _, _>("async_panic", |_, _| async { panic!("async") }).unwrap();
+		module.register_blocking_method::<(), _>("blocking_panic", |_, _| panic!("blocking")).unwrap();
+
+		for (id, method) in ["sync_panic", "async_panic", "blocking_panic"].iter().enumerate() {
+			let req = format!(r#"{{"jsonrpc":"2.0","method":"{}","id":{}}}"#, method, id);
+			let (resp, _) = module.raw_json_request(&req).await.unwrap();
+			assert_eq!(
+				resp,
+				format!(r#"{{"jsonrpc":"2.0","error":{{"code":-32603,"message":"Internal error"}},"id":{}}}"#, id)
+			);
+		}
+	}
+
+	#[tokio::test]
+	async fn panicking_subscription_is_answered_once() {
+		let mut module = RpcModule::new(());
+		module.register_subscription("panic_sub", "panic_sub", "panic_unsub", |_, _, _| panic!("sync")).unwrap();
+		module
+			.register_subscription("late_panic_sub", "late_panic_sub", "late_panic_unsub", |_, mut sink, _| {
+				sink.send(&1)?;
+				panic!("sync")
+			})
+			.unwrap();
+
+		let (resp, mut stream) =
+			module.raw_json_request(r#"{"jsonrpc":"2.0","method":"panic_sub","id":1}"#).await.unwrap();
+		assert_eq!(resp, r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error"},"id":1}"#);
+		assert!(stream.next().await.is_none());
+
+		// Already accepted, so the client only sees the subscription being closed.
+		let (resp, mut stream) =
+			module.raw_json_request(r#"{"jsonrpc":"2.0","method":"late_panic_sub","id":2}"#).await.unwrap();
+		let sub_id = serde_json::from_str::<Response<u64>>(&resp).unwrap().result;
+		let notif = stream.next().await.unwrap();
+		assert_eq!(serde_json::from_str::<SubscriptionResponse<u64>>(&notif).unwrap().params.result, 1);
+		let notif = stream.next().await.unwrap();
+		let closed = serde_json::from_str::<SubscriptionResponse<SubscriptionClosedError>>(&notif).unwrap().params;
+		assert_eq!(closed.subscription, RpcSubscriptionId::Num(sub_id));
+		assert_eq!(closed.result.close_reason(), "Closed by the server");
+		assert!(stream.next().await.is_none());
+	}
+
 	#[tokio::test]
 	async fn calling_method_without_server_using_proc_macro() {
 		use jsonrpsee::{proc_macros::rpc, types::async_trait};
@@ -1080,4 +1263,82 @@ mod tests {
 		my_sub.close();
 		assert!(matches!(my_sub.next::<String>().await, None));
 	}
+
+	#[tokio::test]
+	async fn subscription_is_answered_once() {
+		let mut module = RpcModule::new(());
+		module
+			.register_subscription("reject_sub", "reject_sub", "reject_unsub", |_, sink, _| {
+				sink.reject(ErrorCode::InvalidParams.into());
+				Ok(())
+			})
+			.unwrap();
+		module
+			.register_subscription("failing_sub", "failing_sub", "failing_unsub", |params, _, _| {
+				let _: u64 = params.one()?;
+				Ok(())
+			})
+			.unwrap();
+		module.register_subscription("noop_sub", "noop_sub", "noop_unsub", |_, _, _| Ok(())).unwrap();
+
+		let (resp, mut stream) =
+			module.raw_json_request(r#"{"jsonrpc":"2.0","method":"reject_sub","id":1}"#).await.unwrap();
+		assert_eq!(resp, r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params"},"id":1}"#);
+		assert!(stream.next().await.is_none());
+
+		let (resp, mut stream) =
+			module.raw_json_request(r#"{"jsonrpc":"2.0","method":"failing_sub","id":2}"#).await.unwrap();
+		assert_eq!(resp, r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Server error"},"id":2}"#);
+		assert!(stream.next().await.is_none());
+
+		// Dropping the sink without accepting or rejecting answers with an internal error.
+		let (resp, mut stream) =
+			module.raw_json_request(r#"{"jsonrpc":"2.0","method":"noop_sub","id":3}"#).await.unwrap();
+		assert_eq!(resp, r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error"},"id":3}"#);
+		assert!(stream.next().await.is_none());
+	}
+
+	#[tokio::test]
+	async fn subscription_ids_are_not_reused() {
+		let mut module = RpcModule::new(());
+		// Each subscription is closed right away when the accepted sink is dropped, freeing its ID.
+		for &(sub, unsub) in [("my_sub", "my_unsub"), ("other_sub", "other_unsub")].iter() {
+			module
+				.register_subscription(sub, sub, unsub, |_, mut sink, _| {
+					sink.accept();
+					Ok(())
+				})
+				.unwrap();
+		}
+
+		let mut ids = Vec::new();
+		for method in ["my_sub", "other_sub", "my_sub"].iter() {
+			let req = format!(r#"{{"jsonrpc":"2.0","method":"{}","id":0}}"#, method);
+			let (resp, _) = module.raw_json_request(&req).await.unwrap();
+			ids.push(serde_json::from_str::<Response<u64>>(&resp).unwrap().result);
+		}
+		assert!(ids.windows(2).all(|w| w[0] < w[1]), "IDs must increase, got {:?}", ids);
+	}
+
+	#[tokio::test]
+	async fn subscription_sink_observes_closed_subscription() {
+		let (tx, mut rx) = mpsc::unbounded();
+		let mut module = RpcModule::new(());
+		module
+			.register_subscription("my_sub", "my_sub", "my_unsub", move |_, sink, _| {
+				let tx = tx.clone();
+				std::thread::spawn(move || {
+					while !sink.is_closed() {
+						std::thread::sleep(std::time::Duration::from_millis(10));
+					}
+					tx.unbounded_send(()).unwrap();
+				});
+				Ok(())
+			})
+			.unwrap();
+
+		let mut my_sub = module.subscribe("my_sub", EmptyParams::new()).await.unwrap();
+		my_sub.close();
+		assert!(rx.next().await.is_some());
+	}
 }
@@ -24,37 +24,57 @@
 // IN background_task WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
+use std::convert::TryFrom;
 use std::future::Future;
+use std::io;
 use std::net::SocketAddr;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use crate::future::{FutureDriver, ServerHandle, StopMonitor};
 use crate::types::{
 	error::Error,
 	middleware::Middleware,
-	v2::{ErrorCode, Id, Request},
+	to_json_raw_value,
+	v2::{ErrorCode, ErrorObject, Id, Notification, Request},
 	TEN_MB_SIZE_BYTES,
 };
-use futures_channel::mpsc;
-use futures_util::future::join_all;
+use futures_channel::{mpsc, oneshot};
 use futures_util::future::FutureExt;
-use futures_util::io::{BufReader, BufWriter};
+use futures_util::io::{AsyncRead, AsyncWrite, BufReader, BufWriter};
 use futures_util::stream::StreamExt;
+use serde_json::value::RawValue;
 use soketto::connection::Error as SokettoError;
+use soketto::data::ByteSlice125;
 use soketto::handshake::{server::Response, Server as SokettoServer};
 use soketto::Sender;
 use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::time::{timeout_at, Instant};
 use tokio_util::compat::{Compat, TokioAsyncReadCompatExt};
 
 use jsonrpsee_utils::server::{
-	helpers::{collect_batch_response, prepare_error, MethodSink},
+	helpers::{execute_batch, prepare_error, split_batch, MethodSink},
 	resource_limiting::Resources,
 	rpc_module::{ConnectionId, MethodResult, Methods},
 };
 
+type Notif<'a> = Notification<'a, Option<&'a RawValue>>;
+
+/// Outcome of waiting for the next message on a connection.
+enum Received {
+	/// A data message was read into the buffer.
+	Data,
+	/// Nothing, not even a pong, was received in time after a ping.
+	Inactive,
+}
+
 /// Default maximum connections allowed.
 const MAX_CONNECTIONS: u64 = 100;
+/// Default maximum number of calls in a batch request.
+const MAX_BATCH_LEN: usize = 1024;
 
 /// A WebSocket JSON RPC server.
 #[derive(Debug)]
@@ -210,7 +230,9 @@ where
 	M: Middleware,
 {
 	// For each incoming background_task we perform a handshake.
-	let mut server = SokettoServer::new(BufReader::new(BufWriter::new(socket.compat())));
+	let activity = Arc::new(AtomicUsize::new(0));
+	let socket = ActivityTracker { socket: socket.compat(), reads: activity.clone() };
+	let mut server = SokettoServer::new(BufReader::new(BufWriter::new(socket)));
 
 	match mode {
 		HandshakeResponse::Reject { status_code } => {
@@ -250,10 +272,15 @@ where
 
 			let join_result = tokio::spawn(background_task(
 				server,
+				activity,
 				conn_id,
 				methods.clone(),
 				resources.clone(),
 				cfg.max_request_body_size,
+				cfg.max_batch_len,
+				cfg.batch_request_timeout,
+				cfg.ping_interval,
+				cfg.pong_timeout,
 				stop_monitor.clone(),
 				middleware,
 			))
@@ -267,12 +294,18 @@ where
 	}
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn background_task(
-	server: SokettoServer<'_, BufReader<BufWriter<Compat<tokio::net::TcpStream>>>>,
+	server: SokettoServer<'_, BufReader<BufWriter<ActivityTracker<Compat<tokio::net::TcpStream>>>>>,
+	activity: Arc<AtomicUsize>,
 	conn_id: ConnectionId,
 	methods: Methods,
 	resources: Resources,
 	max_request_body_size: u32,
+	max_batch_len: usize,
+	batch_request_timeout: Option<Duration>,
+	ping_interval: Option<Duration>,
+	pong_timeout: Option<Duration>,
 	stop_server: StopMonitor,
 	middleware: impl Middleware,
 ) -> Result<(), Error> {
@@ -283,19 +316,61 @@ async fn background_task(
 	let (tx, mut rx) = mpsc::unbounded::<String>();
 	let stop_server2 = stop_server.clone();
 	let sink = MethodSink::new_with_limit(tx, max_request_body_size);
+	// Fired when the client didn't send anything in time after a ping.
+	let (inactive_tx, mut inactive_rx) = oneshot::channel::<()>();
 
 	middleware.on_connect();
 
 	// Send results back to the client.
 	tokio::spawn(async move {
+		let mut next_ping = ping_interval.map(|interval| Instant::now() + interval);
+		// Deadline for the client to answer the last ping, along with the activity seen when it was sent.
+		let mut pong_deadline: Option<(Instant, usize)> = None;
+
 		while !stop_server2.shutdown_requested() {
-			match rx.next().await {
+			let deadline = match (next_ping, pong_deadline) {
+				(Some(ping), Some((pong, _))) => Some(ping.min(pong)),
+				(ping, pong) => ping.or(pong.map(|(pong, _)| pong)),
+			};
+
+			let next = match deadline {
+				Some(deadline) => match timeout_at(deadline, rx.next()).await {
+					Ok(next) => next,
+					Err(_) => {
+						let now = Instant::now();
+						if let Some((_, seen)) = pong_deadline.filter(|&(pong, _)| pong <= now) {
+							if activity.load(Ordering::Relaxed) == seen {
+								let _ = inactive_tx.send(());
+								break;
+							}
+							pong_deadline = None;
+						}
+						if next_ping.map_or(false, |ping| ping <= now) {
+							if let Err(err) = send_ws_ping(&mut sender).await {
+								tracing::error!("WS transport error: {:?}; terminate connection", err);
+								break;
+							}
+							next_ping = ping_interval.map(|interval| now + interval);
+							if pong_deadline.is_none() {
+								pong_deadline =
+									pong_timeout.map(|timeout| (now + timeout, activity.load(Ordering::Relaxed)));
+							}
+						}
+						continue;
+					}
+				},
+				None => rx.next().await,
+			};
+
+			match next {
 				Some(response) => {
 					// If websocket message send fail then terminate the connection.
 					if let Err(err) = send_ws_message(&mut sender, response).await {
 						tracing::error!("WS transport error: {:?}; terminate connection", err);
 						break;
 					}
+					// Only idle connections are pinged.
+					next_ping = ping_interval.map(|interval| Instant::now() + interval);
 				}
 				None => break,
 			};
@@ -316,12 +391,24 @@ async fn background_task(
 
 		{
 			// Need the extra scope to drop this pinned future and reclaim access to `data`
-			let receive = receiver.receive_data(&mut data);
+			let receive = async {
+				tokio::select! {
+					received = receiver.receive_data(&mut data) => received.map(|_| Received::Data),
+					// The sender is dropped without firing when the connection is closed otherwise.
+					Ok(()) = &mut inactive_rx => Ok(Received::Inactive),
+				}
+			};
 
 			tokio::pin!(receive);
 
-			if let Err(err) = method_executors.select_with(Monitored::new(receive, &stop_server)).await {
-				match err {
+			match method_executors.select_with(Monitored::new(receive, &stop_server)).await {
+				Ok(Received::Data) => (),
+				Ok(Received::Inactive) => {
+					tracing::warn!("WS transport error: ping not answered => terminating connection {}", conn_id);
+					sink.close();
+					break Ok(());
+				}
+				Err(err) => match err {
 					MonitoredError::Selector(SokettoError::Closed) => {
 						tracing::debug!("WS transport error: remote peer terminated the connection: {}", conn_id);
 						sink.close();
@@ -343,7 +430,7 @@ async fn background_task(
 						break Err(err.into());
 					}
 					MonitoredError::Shutdown => break Ok(()),
-				};
+				},
 			};
 		};
 
@@ -379,6 +466,9 @@ async fn background_task(
 							middleware.on_response(request_start);
 						}
 					}
+				} else if serde_json::from_slice::<Notif>(&data).is_ok() {
+					// The server must not reply to a notification.
+					middleware.on_response(request_start);
 				} else {
 					let (id, code) = prepare_error(&data);
 					sink.send_error(id, code.into());
@@ -393,44 +483,46 @@ async fn background_task(
 				let sink = sink.clone();
 
 				let fut = async move {
-					// Batch responses must be sent back as a single message so we read the results from each
-					// request in the batch and read the results off of a new channel, `rx_batch`, and then send the
-					// complete batch response back to the client over `tx`.
-					let (tx_batch, mut rx_batch) = mpsc::unbounded();
-					let sink_batch = MethodSink::new_with_limit(tx_batch, max_request_body_size);
-					if let Ok(batch) = serde_json::from_slice::<Vec<Request>>(&d) {
+					// Batch responses must be sent back as a single message so the results of all the calls in the
+					// batch are collected by `execute_batch` and the complete batch response is sent back to the
+					// client over `tx`.
+					if let Ok(batch) = serde_json::from_slice::<Vec<&RawValue>>(&d) {
 						tracing::debug!("recv batch len={}", batch.len());
 						tracing::trace!("recv: batch={:?}", batch);
-						if !batch.is_empty() {
-							join_all(batch.into_iter().filter_map(move |req| {
-								match methods.execute_with_resources(&sink_batch, req, conn_id, resources) {
-									Ok((name, MethodResult::Sync(success))) => {
-										middleware.on_result(name, success, request_start);
-										None
-									}
-									Ok((name, MethodResult::Async(fut))) => Some(async move {
-										let success = fut.await;
-										middleware.on_result(name, success, request_start);
-									}),
-									Err(name) => {
-										middleware.on_result(name.as_ref(), false, request_start);
-										None
-									}
-								}
-							}))
-							.await;
 
-							rx_batch.close();
-							let results = collect_batch_response(rx_batch).await;
+						if batch.is_empty() {
+							sink.send_error(Id::Null, ErrorCode::InvalidRequest.into());
+							middleware.on_response(request_start);
+						} else if batch.len() > max_batch_len {
+							let data = to_json_raw_value(&format!("Exceeded max batch limit {}", max_batch_len)).ok();
+							sink.send_error(Id::Null, ErrorObject::new(ErrorCode::InvalidRequest, data.as_deref()));
+							middleware.on_response(request_start);
+						} else {
+							// Notifications in a batch are never answered.
+							let calls = split_batch(&batch);
 
-							if let Err(err) = sink.send_raw(results) {
-								tracing::error!("Error sending batch response to the client: {:?}", err)
-							} else {
+							if calls.is_empty() {
+								// A batch of only notifications gets no response at all.
 								middleware.on_response(request_start);
+							} else {
+								let results = execute_batch(
+									calls,
+									conn_id,
+									methods,
+									resources,
+									middleware,
+									request_start,
+									max_request_body_size,
+									batch_request_timeout,
+								)
+								.await;
+
+								if let Err(err) = sink.send_raw(results) {
+									tracing::error!("Error sending batch response to the client: {:?}", err)
+								} else {
+									middleware.on_response(request_start);
+								}
 							}
-						} else {
-							sink.send_error(Id::Null, ErrorCode::InvalidRequest.into());
-							middleware.on_response(request_start);
 						}
 					} else {
 						let (id, code) = prepare_error(&d);
@@ -484,6 +576,14 @@ struct Settings {
 	max_request_body_size: u32,
 	/// Maximum number of incoming connections allowed.
 	max_connections: u64,
+	/// Maximum number of calls in a batch request.
+	max_batch_len: usize,
+	/// Deadline for answering all calls in a batch request.
+	batch_request_timeout: Option<Duration>,
+	/// Interval without any message sent to the client after which a ping is sent.
+	ping_interval: Option<Duration>,
+	/// Time to wait for anything to be received after a ping before terminating the connection.
+	pong_timeout: Option<Duration>,
 	/// Policy by which to accept or deny incoming requests based on the `Origin` header.
 	allowed_origins: AllowedValue,
 	/// Policy by which to accept or deny incoming requests based on the `Host` header.
@@ -497,6 +597,10 @@ impl Default for Settings {
 		Self {
 			max_request_body_size: TEN_MB_SIZE_BYTES,
 			max_connections: MAX_CONNECTIONS,
+			max_batch_len: MAX_BATCH_LEN,
+			batch_request_timeout: None,
+			ping_interval: None,
+			pong_timeout: None,
 			allowed_origins: AllowedValue::Any,
 			allowed_hosts: AllowedValue::Any,
 			tokio_runtime: None,
@@ -538,6 +642,44 @@ impl<M> Builder<M> {
 		self
 	}
 
+	/// Set the maximum number of calls allowed in a batch request. Default is 1024.
+	///
+	/// Batches exceeding the limit are rejected with a single `Invalid request` error.
+	pub fn max_batch_len(mut self, len: usize) -> Self {
+		self.settings.max_batch_len = len;
+		self
+	}
+
+	/// Set a deadline for answering all calls in a batch request, measured from when the batch was received.
+	///
+	/// Calls that haven't completed once the deadline has passed are answered with an `Internal error` so that
+	/// the batch response can be sent. Disabled by default.
+	pub fn batch_request_timeout(mut self, timeout: Duration) -> Self {
+		self.settings.batch_request_timeout = Some(timeout);
+		self
+	}
+
+	/// Send a WebSocket ping to the client once nothing was sent to it for `interval`.
+	///
+	/// This keeps idle connections, such as subscriptions waiting for data, from being closed by proxies. Every
+	/// response or notification restarts the interval. A connection where the ping can't be delivered is
+	/// terminated. Disabled by default.
+	pub fn ping_interval(mut self, interval: Duration) -> Self {
+		self.settings.ping_interval = Some(interval);
+		self
+	}
+
+	/// Terminate connections that stop answering pings: if nothing, not even a pong, is received from the client
+	/// within `timeout` after sending a ping, the connection and all its subscriptions are closed.
+	///
+	/// Any data received counts, so a client that is still uploading a large message is not disconnected.
+	///
+	/// Only takes effect together with [`WsServerBuilder::ping_interval`]. Disabled by default.
+	pub fn pong_timeout(mut self, timeout: Duration) -> Self {
+		self.settings.pong_timeout = Some(timeout);
+		self
+	}
+
 	/// Register a new resource kind. Errors if `label` is already registered, or if the number of
 	/// registered resources on this server instance would exceed 8.
 	///
@@ -681,7 +823,7 @@ impl<M> Builder<M> {
 }
 
 async fn send_ws_message(
-	sender: &mut Sender<BufReader<BufWriter<Compat<TcpStream>>>>,
+	sender: &mut Sender<BufReader<BufWriter<ActivityTracker<Compat<TcpStream>>>>>,
 	response: String,
 ) -> Result<(), Error> {
 	tracing::debug!("send {} bytes", response.len());
@@ -689,3 +831,44 @@ async fn send_ws_message(
 	sender.send_text_owned(response).await?;
 	sender.flush().await.map_err(Into::into)
 }
+
+async fn send_ws_ping(
+	sender: &mut Sender<BufReader<BufWriter<ActivityTracker<Compat<TcpStream>>>>>,
+) -> Result<(), Error> {
+	tracing::debug!("send ping");
+	let empty = ByteSlice125::try_from(&[][..]).expect("empty slice is shorter than 125 bytes; qed");
+	sender.send_ping(empty).await?;
+	sender.flush().await.map_err(Into::into)
+}
+
+/// Socket wrapper counting the reads that returned data, to tell whether the client still sends anything at all.
+struct ActivityTracker<S> {
+	socket: S,
+	reads: Arc<AtomicUsize>,
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for ActivityTracker<S> {
+	fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+		let poll = Pin::new(&mut self.socket).poll_read(cx, buf);
+		if let Poll::Ready(Ok(n)) = poll {
+			if n > 0 {
+				self.reads.fetch_add(1, Ordering::Relaxed);
+			}
+		}
+		poll
+	}
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for ActivityTracker<S> {
+	fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+		Pin::new(&mut self.socket).poll_write(cx, buf)
+	}
+
+	fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+		Pin::new(&mut self.socket).poll_flush(cx)
+	}
+
+	fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+		Pin::new(&mut self.socket).poll_close(cx)
+	}
+}
@@ -71,7 +71,7 @@ async fn server() -> SocketAddr {
 ///     async: `say_hello_async` and `add_sync`
 ///     other: `invalid_params` (always returns `CallError::InvalidParams`),
 ///            `call_fail` (always returns `CallError::Failed`),
-///            `sleep_for`
+///            `sleep_for`, `sleep_for_async`
 ///            `subscribe_hello` (starts a subscription that doesn't send anything)
 ///
 /// Returns the address together with handle for the server.
@@ -119,6 +119,13 @@ async fn server_with_handles() -> (SocketAddr, ServerHandle) {
 			Ok("Yawn!")
 		})
 		.unwrap();
+	module
+		.register_async_method("sleep_for_async", |params, _| async move {
+			let sleep: u64 = params.one()?;
+			tokio::time::sleep(Duration::from_millis(sleep)).await;
+			Ok(sleep)
+		})
+		.unwrap();
 	module
 		.register_subscription("subscribe_hello", "subscribe_hello", "unsubscribe_hello", |_, sink, _| {
 			std::thread::spawn(move || loop {
@@ -205,6 +212,298 @@ async fn can_set_the_max_request_body_size() {
 	handle.stop().unwrap();
 }
 
+#[tokio::test]
+async fn can_set_the_max_batch_len() {
+	init_logger();
+
+	let addr = "127.0.0.1:0";
+	let server = WsServerBuilder::default().max_batch_len(2).build(addr).await.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _| Ok("hello")).unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(module).unwrap();
+
+	let mut client = WebSocketTestClient::new(addr).await.unwrap();
+
+	let req = r#"[{"jsonrpc":"2.0","method":"say_hello","id":1},{"jsonrpc":"2.0","method":"say_hello","id":2}]"#;
+	let response = client.send_request_text(req).await.unwrap();
+	assert_eq!(response, r#"[{"jsonrpc":"2.0","result":"hello","id":1},{"jsonrpc":"2.0","result":"hello","id":2}]"#);
+
+	// Invalid: too many calls in the batch
+	let req = r#"[{"jsonrpc":"2.0","method":"say_hello","id":1},{"jsonrpc":"2.0","method":"say_hello","id":2},{"jsonrpc":"2.0","method":"say_hello","id":3}]"#;
+	let response = client.send_request_text(req).await.unwrap();
+	assert_eq!(response, batch_too_large(2));
+
+	handle.stop().unwrap();
+}
+
+#[tokio::test]
+async fn can_set_the_batch_request_timeout() {
+	init_logger();
+
+	let addr = "127.0.0.1:0";
+	let server =
+		WsServerBuilder::default().batch_request_timeout(Duration::from_millis(100)).build(addr).await.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _| Ok("hello")).unwrap();
+	module
+		.register_async_method("sleep_for_async", |params, _| async move {
+			let sleep: u64 = params.one()?;
+			tokio::time::sleep(Duration::from_millis(sleep)).await;
+			Ok(sleep)
+		})
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(module).unwrap();
+
+	let mut client = WebSocketTestClient::new(addr).await.unwrap();
+
+	let req = r#"[{"jsonrpc":"2.0","method":"sleep_for_async","params":[10000],"id":1},{"jsonrpc":"2.0","method":"say_hello","id":2}]"#;
+	let response = client.send_request_text(req).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(
+		response,
+		format!("[{},{}]", internal_error(Id::Num(1)), r#"{"jsonrpc":"2.0","result":"hello","id":2}"#)
+	);
+
+	handle.stop().unwrap();
+}
+
+#[tokio::test]
+async fn panicking_method_does_not_close_the_connection() {
+	init_logger();
+
+	let server = WsServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method::<(), _>("panic", |_, _| panic!("boom")).unwrap();
+	module.register_method("say_hello", |_, _| Ok("hello")).unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(module).unwrap();
+
+	let mut client = WebSocketTestClient::new(addr).await.unwrap();
+
+	let req = r#"{"jsonrpc":"2.0","method":"panic","id":1}"#;
+	let response = client.send_request_text(req).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response, internal_error(Id::Num(1)));
+
+	let req = r#"{"jsonrpc":"2.0","method":"say_hello","id":2}"#;
+	let response = client.send_request_text(req).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(response, ok_response(JsonValue::String("hello".to_owned()), Id::Num(2)));
+
+	handle.stop().unwrap();
+}
+
+#[tokio::test]
+async fn ping_is_sent_on_idle_connection() {
+	use futures_util::io::AsyncReadExt;
+	use soketto::handshake::{Client, ServerResponse};
+	use tokio_util::compat::TokioAsyncReadCompatExt;
+
+	init_logger();
+
+	let addr = "127.0.0.1:0";
+	let server = WsServerBuilder::default().ping_interval(Duration::from_millis(50)).build(addr).await.unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(RpcModule::new(())).unwrap();
+
+	let socket = tokio::net::TcpStream::connect(addr).await.unwrap();
+	let mut client = Client::new(socket.compat(), "test-client", "/");
+	assert!(matches!(client.handshake().await.unwrap(), ServerResponse::Accepted { .. }));
+
+	// Read the raw frame: an unmasked ping with an empty payload.
+	let mut socket = client.into_inner();
+	let mut frame = [0; 2];
+	socket.read_exact(&mut frame).with_default_timeout().await.unwrap().unwrap();
+	assert_eq!(frame, [0x89, 0x00]);
+
+	handle.stop().unwrap();
+}
+
+#[tokio::test]
+async fn connection_is_closed_when_pings_are_not_answered() {
+	use futures_util::io::AsyncReadExt;
+	use soketto::handshake::{Client, ServerResponse};
+	use tokio_util::compat::TokioAsyncReadCompatExt;
+
+	init_logger();
+
+	let addr = "127.0.0.1:0";
+	let server = WsServerBuilder::default()
+		.ping_interval(Duration::from_millis(50))
+		.pong_timeout(Duration::from_millis(50))
+		.build(addr)
+		.await
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let handle = server.start(RpcModule::new(())).unwrap();
+
+	let socket = tokio::net::TcpStream::connect(addr).await.unwrap();
+	let mut client = Client::new(socket.compat(), "test-client", "/");
+	assert!(matches!(client.handshake().await.unwrap(), ServerResponse::Accepted { .. }));
+
+	// Read raw frames without ever answering the pings, until the server closes the connection.
+	let mut socket = client.into_inner();
+	let mut frames = Vec::new();
+	socket.read_to_end(&mut frames).with_default_timeout().await.unwrap().unwrap();
+	assert!(frames.starts_with(&[0x89, 0x00]));
+
+	handle.stop().unwrap();
+}
+
+#[tokio::test]
+async fn connection_answering_pings_is_kept_alive() {
+	use soketto::handshake::{Client, ServerResponse};
+	use tokio_util::compat::TokioAsyncReadCompatExt;
+
+	init_logger();
+
+	let addr = "127.0.0.1:0";
+	let server = WsServerBuilder::default()
+		.ping_interval(Duration::from_millis(50))
+		.pong_timeout(Duration::from_millis(50))
+		.build(addr)
+		.await
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _| Ok("hello")).unwrap();
+	let handle = server.start(module).unwrap();
+
+	let socket = tokio::net::TcpStream::connect(addr).await.unwrap();
+	let mut client = Client::new(socket.compat(), "test-client", "/");
+	assert!(matches!(client.handshake().await.unwrap(), ServerResponse::Accepted { .. }));
+	let (mut sender, mut receiver) = client.into_builder().finish();
+
+	// The soketto receiver answers pings while it waits for data.
+	let response = tokio::spawn(async move {
+		let mut data = Vec::new();
+		receiver.receive_data(&mut data).await.map(|_| String::from_utf8(data).unwrap())
+	});
+
+	tokio::time::sleep(Duration::from_millis(500)).await;
+	sender.send_text(call("say_hello", Vec::<()>::new(), Id::Num(1))).await.unwrap();
+	sender.flush().await.unwrap();
+
+	let response = response.with_default_timeout().await.unwrap().unwrap().unwrap();
+	assert_eq!(response, ok_response("hello".into(), Id::Num(1)));
+
+	handle.stop().unwrap();
+}
+
+/// Masked client text frame carrying `payload`; the masking key is all zeros so the payload goes out as is.
+fn client_text_frame(payload: &str) -> Vec<u8> {
+	let mut frame = vec![0x81];
+	match payload.len() {
+		len if len < 126 => frame.push(0x80 | len as u8),
+		len => {
+			frame.push(0x80 | 126);
+			frame.extend_from_slice(&(len as u16).to_be_bytes());
+		}
+	}
+	frame.extend_from_slice(&[0; 4]);
+	frame.extend_from_slice(payload.as_bytes());
+	frame
+}
+
+/// Splits raw unmasked server frames with short payloads into `(opcode, payload)` pairs.
+fn server_frames(mut raw: &[u8]) -> Vec<(u8, &[u8])> {
+	let mut frames = Vec::new();
+	while raw.len() >= 2 && raw.len() >= 2 + raw[1] as usize {
+		let len = raw[1] as usize;
+		assert!(len < 126, "long frames are not supported");
+		frames.push((raw[0] & 0x0f, &raw[2..2 + len]));
+		raw = &raw[2 + len..];
+	}
+	frames
+}
+
+#[tokio::test]
+async fn ping_interval_restarts_on_every_message() {
+	use futures_util::io::{AsyncReadExt, AsyncWriteExt};
+	use soketto::handshake::{Client, ServerResponse};
+	use tokio_util::compat::TokioAsyncReadCompatExt;
+
+	init_logger();
+
+	let addr = "127.0.0.1:0";
+	let server = WsServerBuilder::default().ping_interval(Duration::from_millis(100)).build(addr).await.unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _| Ok("hello")).unwrap();
+	let handle = server.start(module).unwrap();
+
+	let socket = tokio::net::TcpStream::connect(addr).await.unwrap();
+	let mut client = Client::new(socket.compat(), "test-client", "/");
+	assert!(matches!(client.handshake().await.unwrap(), ServerResponse::Accepted { .. }));
+	let mut socket = client.into_inner();
+
+	// A response every 30 ms keeps the connection busy for longer than the ping interval.
+	for i in 0..10 {
+		socket.write_all(&client_text_frame(&call("say_hello", Vec::<()>::new(), Id::Num(i)))).await.unwrap();
+		tokio::time::sleep(Duration::from_millis(30)).await;
+	}
+
+	let mut raw = Vec::new();
+	let mut buf = [0; 1024];
+	while server_frames(&raw).iter().filter(|(opcode, _)| *opcode == 0x1).count() < 10 {
+		let n = socket.read(&mut buf).with_default_timeout().await.unwrap().unwrap();
+		assert!(n > 0);
+		raw.extend_from_slice(&buf[..n]);
+	}
+
+	let frames = server_frames(&raw);
+	let answered = frames.iter().position(|(opcode, payload)| *opcode == 0x1 && payload.ends_with(br#""id":9}"#));
+	assert!(frames[..=answered.unwrap()].iter().all(|(opcode, _)| *opcode == 0x1), "unexpected ping: {:?}", frames);
+
+	handle.stop().unwrap();
+}
+
+#[tokio::test]
+async fn slow_upload_is_not_mistaken_for_missing_pong() {
+	use futures_util::io::{AsyncReadExt, AsyncWriteExt};
+	use soketto::handshake::{Client, ServerResponse};
+	use tokio_util::compat::TokioAsyncReadCompatExt;
+
+	init_logger();
+
+	let addr = "127.0.0.1:0";
+	let server = WsServerBuilder::default()
+		.ping_interval(Duration::from_millis(50))
+		.pong_timeout(Duration::from_millis(50))
+		.build(addr)
+		.await
+		.unwrap();
+	let addr = server.local_addr().unwrap();
+	let mut module = RpcModule::new(());
+	module.register_method("say_hello", |_, _| Ok("hello")).unwrap();
+	let handle = server.start(module).unwrap();
+
+	let socket = tokio::net::TcpStream::connect(addr).await.unwrap();
+	let mut client = Client::new(socket.compat(), "test-client", "/");
+	assert!(matches!(client.handshake().await.unwrap(), ServerResponse::Accepted { .. }));
+	let mut socket = client.into_inner();
+
+	// The pings are never answered, but the client keeps sending a single message for half a second.
+	let req = format!("{:<400}", call("say_hello", Vec::<()>::new(), Id::Num(1)));
+	for chunk in client_text_frame(&req).chunks(20) {
+		socket.write_all(chunk).await.unwrap();
+		tokio::time::sleep(Duration::from_millis(25)).await;
+	}
+
+	let mut raw = Vec::new();
+	let mut buf = [0; 1024];
+	while !server_frames(&raw).iter().any(|(opcode, _)| *opcode == 0x1) {
+		let n = socket.read(&mut buf).with_default_timeout().await.unwrap().unwrap();
+		assert!(n > 0, "connection closed, frames: {:?}", server_frames(&raw));
+		raw.extend_from_slice(&buf[..n]);
+	}
+
+	let frames = server_frames(&raw);
+	let response = frames.iter().find(|(opcode, _)| *opcode == 0x1).unwrap().1;
+	assert_eq!(std::str::from_utf8(response).unwrap(), ok_response("hello".into(), Id::Num(1)));
+
+	handle.stop().unwrap();
+}
+
 #[tokio::test]
 async fn can_set_max_connections() {
 	let addr = "127.0.0.1:0";
@@ -291,6 +590,28 @@ async fn batch_method_call_works() {
 	);
 }
 
+#[tokio::test]
+async fn batch_method_call_preserves_request_order() {
+	let addr = server().await;
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+
+	// The calls complete in the reverse order of how they were sent.
+	let batch = [
+		r#"{"jsonrpc":"2.0","method":"sleep_for_async","params":[300],"id":1}"#,
+		r#"{"jsonrpc":"2.0","method":"sleep_for_async","params":[200],"id":2}"#,
+		r#"{"jsonrpc":"2.0","method":"sleep_for_async","params":[100],"id":3}"#,
+		r#"{"jsonrpc":"2.0","method":"say_hello","id":4}"#,
+	];
+	let batch = format!("[{}]", batch.join(","));
+
+	let response = client.send_request_text(batch).with_default_timeout().await.unwrap().unwrap();
+
+	assert_eq!(
+		response,
+		r#"[{"jsonrpc":"2.0","result":300,"id":1},{"jsonrpc":"2.0","result":200,"id":2},{"jsonrpc":"2.0","result":100,"id":3},{"jsonrpc":"2.0","result":"hello","id":4}]"#
+	);
+}
+
 #[tokio::test]
 async fn batch_method_call_where_some_calls_fail() {
 	let addr = server().await;
@@ -311,6 +632,51 @@ async fn batch_method_call_where_some_calls_fail() {
 	);
 }
 
+#[tokio::test]
+async fn batch_method_call_with_notifications() {
+	let addr = server().await;
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+
+	let batch = r#"[{"jsonrpc":"2.0","method":"say_hello","params":[]},{"jsonrpc":"2.0","method":"say_hello","id":1}]"#;
+	let response = client.send_request_text(batch).with_default_timeout().await.unwrap().unwrap();
+
+	assert_eq!(response, r#"[{"jsonrpc":"2.0","result":"hello","id":1}]"#);
+}
+
+#[tokio::test]
+async fn batch_method_call_with_duplicate_ids() {
+	let addr = server().await;
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+
+	let batch = [
+		r#"{"jsonrpc":"2.0","method":"say_hello","id":"a"}"#,
+		r#"{"jsonrpc":"2.0","method":"say_hello","id":"a"}"#,
+		r#"{"jsonrpc":"2.0","method":"add","params":[34, 45],"id":3}"#,
+	];
+	let batch = format!("[{}]", batch.join(","));
+
+	let response = client.send_request_text(batch).with_default_timeout().await.unwrap().unwrap();
+
+	assert_eq!(
+		response,
+		format!(
+			"[{},{},{}]",
+			invalid_request(Id::Str("a".into())),
+			invalid_request(Id::Str("a".into())),
+			r#"{"jsonrpc":"2.0","result":79,"id":3}"#
+		)
+	);
+}
+
+#[tokio::test]
+async fn notification_is_not_answered() {
+	let addr = server().await;
+	let mut client = WebSocketTestClient::new(addr).with_default_timeout().await.unwrap().unwrap();
+
+	let notif = r#"{"jsonrpc":"2.0","method":"say_hello","params":[]}"#;
+	assert!(client.send_request_text(notif).with_timeout(Duration::from_millis(500)).await.is_err());
+}
+
 #[tokio::test]
 async fn garbage_request_fails() {
 	let addr = server().await;